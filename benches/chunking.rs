@@ -27,6 +27,8 @@
 //!   avg_size     — iterator across 16KiB / 1MiB / 2MiB average chunk sizes
 //!   small        — sub-min and tiny inputs (per-call overhead)
 //!   versions     — v2016 vs v2020 vs ronomon, same input
+//!   cut_gear     — dispatched (AVX-512/AVX2) vs scalar v2020::cut_gear, across
+//!                  4KiB / 16KiB / 64KiB / 256KiB average chunk sizes
 
 use std::hint::black_box;
 use std::io::Cursor;
@@ -158,6 +160,16 @@ fn run_v2020_stream(data: &[u8], min: usize, avg: usize, max: usize) -> usize {
     acc
 }
 
+fn run_v2020_lending(data: &[u8], min: usize, avg: usize, max: usize) -> usize {
+    let mut chunker = v2020::LendingStreamCDC::new(Cursor::new(data), min, avg, max);
+    let mut acc = 0usize;
+    while let Some(r) = chunker.next_chunk() {
+        let c = r.expect("lending chunk");
+        acc ^= c.length() ^ (c.hash as usize);
+    }
+    acc
+}
+
 fn run_v2016_iter(data: &[u8], min: usize, avg: usize, max: usize) -> usize {
     let chunker = v2016::FastCDC::new(data, min, avg, max);
     let mut acc = 0usize;
@@ -176,6 +188,39 @@ fn run_ronomon_iter(data: &[u8], min: usize, avg: usize, max: usize) -> usize {
     acc
 }
 
+// Scan the whole input with `v2020::cut_gear` (vector instructions where the
+// processor and average size allow) or `v2020::cut_gear_scalar`.
+fn run_v2020_cut_gear(data: &[u8], min: usize, avg: usize, max: usize, scalar: bool) -> usize {
+    let bits = avg.ilog2() as usize;
+    let mask_s = v2020::MASKS[bits + 1];
+    let mask_l = v2020::MASKS[bits - 1];
+    let (gear, gear_ls) = v2020::get_gear_with_seed(0);
+    let cut = if scalar {
+        v2020::cut_gear_scalar
+    } else {
+        v2020::cut_gear
+    };
+    let mut pos = 0usize;
+    let mut acc = 0usize;
+    while pos < data.len() {
+        let (hash, len) = cut(
+            &data[pos..],
+            min,
+            avg,
+            max,
+            mask_s,
+            mask_l,
+            mask_s << 1,
+            mask_l << 1,
+            &gear,
+            &gear_ls,
+        );
+        acc ^= len ^ (hash as usize);
+        pos += len;
+    }
+    acc
+}
+
 // avg -> (min, avg, max) using the crate's example convention (min=avg/4, max=avg*4).
 fn sizes(avg: usize) -> (usize, usize, usize) {
     (avg / 4, avg, avg * 4)
@@ -201,6 +246,9 @@ fn bench_v2020_paths(c: &mut Criterion) {
     g.bench_function("stream", |b| {
         b.iter(|| black_box(run_v2020_stream(black_box(&data), min, avg, max)))
     });
+    g.bench_function("lending", |b| {
+        b.iter(|| black_box(run_v2020_lending(black_box(&data), min, avg, max)))
+    });
     g.finish();
 }
 
@@ -272,6 +320,22 @@ fn bench_versions(c: &mut Criterion) {
     g.finish();
 }
 
+fn bench_cut_gear(c: &mut Criterion) {
+    let data = gen_random(16 * MIB, 31);
+    let mut g = c.benchmark_group("cut_gear");
+    g.throughput(Throughput::Bytes(data.len() as u64));
+    for &avg in &[4 * 1024usize, 16 * 1024, 64 * 1024, 256 * 1024] {
+        let (min, avg, max) = sizes(avg);
+        for (path, scalar) in [("dispatch", false), ("scalar", true)] {
+            let label = format!("{path}_{}KiB", avg / 1024);
+            g.bench_function(&label, |b| {
+                b.iter(|| black_box(run_v2020_cut_gear(black_box(&data), min, avg, max, scalar)))
+            });
+        }
+    }
+    g.finish();
+}
+
 criterion_group!(
     benches,
     bench_v2020_paths,
@@ -279,5 +343,6 @@ criterion_group!(
     bench_avg_size,
     bench_small,
     bench_versions,
+    bench_cut_gear,
 );
 criterion_main!(benches);
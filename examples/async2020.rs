@@ -30,7 +30,7 @@ async fn main() {
     let min_size = avg_size / 4;
     let max_size = avg_size * 4;
     let mut chunker = AsyncStreamCDC::new(file, min_size, avg_size, max_size);
-    let mut stream = Box::pin(chunker.as_stream());
+    let mut stream = Box::pin(chunker.as_tokio_stream());
     while let Some(result) = stream.next().await {
         let entry = result.expect("failed to read chunk");
         println!(
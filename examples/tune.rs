@@ -0,0 +1,44 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+use clap::{Arg, ArgAction, arg, command, value_parser};
+use fastcdc::tuner::Tuner;
+use memmap2::Mmap;
+use std::fs::File;
+
+fn main() {
+    let matches = command!("Example of tuning the v2020 chunker.")
+        .about("Compares chunking parameters on sample files and recommends one.")
+        .arg(
+            arg!(
+                -s --size <SIZE> "The target average size of the chunks."
+            )
+            .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("INPUT")
+                .help("Sets the sample files to use")
+                .required(true)
+                .action(ArgAction::Append)
+                .index(1),
+        )
+        .get_matches();
+    let size = matches.get_one::<usize>("size").unwrap_or(&16384);
+    let mut mmaps = Vec::new();
+    for filename in matches.get_many::<String>("INPUT").unwrap() {
+        let file = File::open(filename).expect("cannot open file!");
+        mmaps.push(unsafe { Mmap::map(&file).expect("cannot create mmap?") });
+    }
+    let samples: Vec<&[u8]> = mmaps.iter().map(|m| &m[..]).collect();
+    let report = Tuner::new(*size).run(&samples, &mut blake3::Hasher::new());
+    println!("{report}");
+    if let Some(best) = report.recommendation() {
+        println!(
+            "recommended: min_size={} avg_size={} max_size={} normalization={}",
+            best.config.min_size(),
+            best.config.avg_size(),
+            best.config.max_size(),
+            best.config.normalization()
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`AsyncSource`], the trait by which the `AsyncStreamCDC`
+//! of each module reads from a source that implements the `AsyncRead` trait of
+//! either `tokio` or `futures`, along with the buffering shared by those
+//! chunkers.
+//!
+//! The type parameter of the trait, `Tokio` or `Futures`, names the
+//! `AsyncRead` trait in question, such that both implementations can exist
+//! when both features are enabled. The `as_stream()` and `into_stream()`
+//! methods of the chunkers are generic over that parameter, which the compiler
+//! infers from the type of the source, unless the source implements both
+//! traits, as `&[u8]` does. For such a source, use the `as_tokio_stream()` or
+//! `as_futures_stream()` methods, and their `into_*` counterparts, instead.
+use core::future::{Future, poll_fn};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+#[cfg(feature = "futures")]
+pub(crate) use futures::stream::Stream;
+use std::io;
+#[cfg(all(feature = "tokio", not(feature = "futures")))]
+pub(crate) use tokio_stream::Stream;
+
+/// Names the `AsyncRead` trait of `tokio`.
+#[cfg(feature = "tokio")]
+pub enum Tokio {}
+
+/// Names the `AsyncRead` trait of `futures`.
+#[cfg(feature = "futures")]
+pub enum Futures {}
+
+mod private {
+    pub trait Sealed<B> {}
+}
+
+///
+/// A source of bytes for the async chunkers, implemented for every reader that
+/// implements the `AsyncRead` trait named by `B`, and is `Unpin`.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+///
+pub trait AsyncSource<B>: private::Sealed<B> + Unpin {
+    ///
+    /// Attempt to read bytes into `buf`, returning the number of bytes read,
+    /// which is zero at the end of the source.
+    ///
+    fn poll_read_into(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> private::Sealed<Tokio> for R {}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncSource<Tokio> for R {
+    fn poll_read_into(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        tokio::io::AsyncRead::poll_read(self, cx, &mut buf).map_ok(|()| buf.filled().len())
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: futures::io::AsyncRead + Unpin> private::Sealed<Futures> for R {}
+
+#[cfg(feature = "futures")]
+impl<R: futures::io::AsyncRead + Unpin> AsyncSource<Futures> for R {
+    fn poll_read_into(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        futures::io::AsyncRead::poll_read(self, cx, buf)
+    }
+}
+
+///
+/// Buffer of `capacity` bytes read from the source, in which the async
+/// chunkers find the cut points.
+///
+pub(crate) struct AsyncBuffer<R> {
+    /// Buffer of data from source for finding cut points.
+    buffer: Vec<u8>,
+    /// Maximum capacity of the buffer (always `max_size`).
+    capacity: usize,
+    /// Number of relevant bytes in the `buffer`.
+    length: usize,
+    /// Source from which data is read into `buffer`.
+    source: R,
+    /// Number of bytes read from the source so far.
+    processed: u64,
+    /// True when the source produces no more data.
+    eof: bool,
+}
+
+impl<R> AsyncBuffer<R> {
+    pub(crate) fn new(source: R, capacity: usize) -> Self {
+        Self {
+            buffer: vec![0_u8; capacity],
+            capacity,
+            length: 0,
+            source,
+            processed: 0,
+            eof: false,
+        }
+    }
+
+    /// The bytes read from the source and not yet drained.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.buffer[..self.length]
+    }
+
+    /// True when the source produces no more data.
+    pub(crate) fn eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Drains a specified number of bytes from the buffer, returning their
+    /// offset within the source and a copy of the bytes.
+    pub(crate) fn drain_bytes(&mut self, count: usize) -> (u64, Vec<u8>) {
+        // this code originally copied from asuran crate
+        debug_assert!(count <= self.length);
+        let offset = self.processed;
+        self.processed += count as u64;
+        let data = self.buffer[..count].to_vec();
+        self.buffer.copy_within(count..self.length, 0);
+        self.length -= count;
+        (offset, data)
+    }
+
+    /// Fill the buffer with data from the source, returning the number of bytes
+    /// read (zero if end of source has been reached).
+    pub(crate) async fn fill_buffer<B>(&mut self) -> io::Result<usize>
+    where
+        R: AsyncSource<B>,
+    {
+        // this code originally copied from asuran crate
+        let mut all_bytes_read = 0;
+        while !self.eof && self.length < self.capacity {
+            let buf = &mut self.buffer[self.length..];
+            let source = &mut self.source;
+            let bytes_read = poll_fn(|cx| Pin::new(&mut *source).poll_read_into(cx, buf)).await?;
+            if bytes_read == 0 {
+                self.eof = true;
+            } else {
+                self.length += bytes_read;
+                all_bytes_read += bytes_read;
+            }
+        }
+        Ok(all_bytes_read)
+    }
+}
+
+///
+/// Produce a stream of the values returned by `next`, which is given the state
+/// and returns the next value and state, or `None` at the end of the stream.
+///
+pub(crate) fn unfold<S, T, F, Fut>(state: S, next: F) -> impl Stream<Item = T>
+where
+    F: FnMut(S) -> Fut,
+    Fut: Future<Output = Option<(T, S)>>,
+{
+    #[cfg(feature = "futures")]
+    {
+        futures::stream::unfold(state, next)
+    }
+    #[cfg(not(feature = "futures"))]
+    {
+        let mut next = next;
+        async_stream::stream! {
+            let mut state = state;
+            while let Some((item, following)) = next(state).await {
+                state = following;
+                yield item;
+            }
+        }
+    }
+}
+
+///
+/// Define the methods of the `AsyncStreamCDC` of the calling module that
+/// produce streams of its chunks, given its `read_chunk()` method, which finds
+/// the next chunk in the source, and its `ChunkData` and `Error` types.
+///
+macro_rules! async_streams {
+    () => {
+        impl<R> AsyncStreamCDC<R> {
+            ///
+            /// Produce a stream of the chunks read from a source that
+            /// implements the `AsyncRead` trait named by `B`, either `Tokio` or
+            /// `Futures`.
+            ///
+            /// The compiler infers `B` from the type of the source, unless the
+            /// source implements both traits, as `&[u8]` does, in which case
+            /// use `as_tokio_stream()` or `as_futures_stream()` instead.
+            ///
+            pub fn as_stream<B>(
+                &mut self,
+            ) -> impl $crate::async_source::Stream<Item = Result<ChunkData, Error>> + '_
+            where
+                R: $crate::async_source::AsyncSource<B>,
+            {
+                self.stream()
+            }
+
+            ///
+            /// Consume the chunker and produce a stream of the chunks read from
+            /// a source that implements the `AsyncRead` trait named by `B`, as
+            /// with [`as_stream`](AsyncStreamCDC::as_stream).
+            ///
+            /// The stream owns the chunker, so it can be stored alongside other
+            /// values or moved into a spawned task.
+            ///
+            pub fn into_stream<B>(
+                self,
+            ) -> impl $crate::async_source::Stream<Item = Result<ChunkData, Error>> + Send + 'static
+            where
+                R: $crate::async_source::AsyncSource<B> + Send + 'static,
+                B: 'static,
+            {
+                self.owned_stream()
+            }
+
+            /// Produce a stream of the chunks read from the source.
+            fn stream<B>(
+                &mut self,
+            ) -> impl $crate::async_source::Stream<Item = Result<ChunkData, Error>> + '_
+            where
+                R: $crate::async_source::AsyncSource<B>,
+            {
+                $crate::async_source::unfold(self, |this| async move {
+                    let chunk = this.read_chunk().await?;
+                    Some((chunk, this))
+                })
+            }
+
+            /// Consume the chunker and produce a stream of the chunks read from
+            /// the source.
+            fn owned_stream<B>(
+                self,
+            ) -> impl $crate::async_source::Stream<Item = Result<ChunkData, Error>>
+            where
+                R: $crate::async_source::AsyncSource<B>,
+            {
+                $crate::async_source::unfold(self, |mut this| async move {
+                    let chunk = this.read_chunk().await?;
+                    Some((chunk, this))
+                })
+            }
+        }
+
+        #[cfg(feature = "tokio")]
+        impl<R: tokio::io::AsyncRead + Unpin> AsyncStreamCDC<R> {
+            ///
+            /// Produce a stream of the chunks read from a `tokio` source.
+            ///
+            pub fn as_tokio_stream(
+                &mut self,
+            ) -> impl tokio_stream::Stream<Item = Result<ChunkData, Error>> + '_ {
+                self.stream::<$crate::async_source::Tokio>()
+            }
+
+            ///
+            /// Consume the chunker and produce a stream of the chunks read from
+            /// a `tokio` source.
+            ///
+            /// Unlike [`as_tokio_stream`](AsyncStreamCDC::as_tokio_stream), the
+            /// stream owns the chunker, so it can be stored alongside other
+            /// values or moved into a spawned task.
+            ///
+            pub fn into_tokio_stream(
+                self,
+            ) -> impl tokio_stream::Stream<Item = Result<ChunkData, Error>> + Send + 'static
+            where
+                R: Send + 'static,
+            {
+                self.owned_stream::<$crate::async_source::Tokio>()
+            }
+        }
+
+        #[cfg(feature = "futures")]
+        impl<R: futures::io::AsyncRead + Unpin> AsyncStreamCDC<R> {
+            ///
+            /// Produce a stream of the chunks read from a `futures` source.
+            ///
+            pub fn as_futures_stream(
+                &mut self,
+            ) -> impl futures::stream::Stream<Item = Result<ChunkData, Error>> + '_ {
+                self.stream::<$crate::async_source::Futures>()
+            }
+
+            ///
+            /// Consume the chunker and produce a stream of the chunks read from
+            /// a `futures` source.
+            ///
+            /// Unlike [`as_futures_stream`](AsyncStreamCDC::as_futures_stream),
+            /// the stream owns the chunker, so it can be stored alongside other
+            /// values or moved into a spawned task.
+            ///
+            pub fn into_futures_stream(
+                self,
+            ) -> impl futures::stream::Stream<Item = Result<ChunkData, Error>> + Send + 'static
+            where
+                R: Send + 'static,
+            {
+                self.owned_stream::<$crate::async_source::Futures>()
+            }
+        }
+    };
+}
+pub(crate) use async_streams;
@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! Helpers for `#[serde(with = "crate::bytes")]`, which serialize a byte vector
+//! as a byte string rather than a sequence of numbers, the latter taking up to
+//! twice as much space in binary formats such as CBOR.
+
+#[cfg(feature = "std")]
+use core::fmt;
+#[cfg(feature = "std")]
+use serde::de::{Deserializer, Visitor};
+use serde::de::{Error, SeqAccess};
+#[cfg(feature = "std")]
+use serde::ser::Serializer;
+
+#[cfg(feature = "std")]
+pub(crate) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(data)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+///
+/// Accepts a byte string, or a sequence of numbers, which is how the formats
+/// that lack a byte string type, such as JSON, encode a byte string.
+///
+#[cfg(feature = "std")]
+pub(crate) struct BytesVisitor;
+
+#[cfg(feature = "std")]
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string")
+    }
+
+    fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(value)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            data.push(byte);
+        }
+        Ok(data)
+    }
+}
+
+///
+/// Read a sequence of exactly `N` numbers into an array, for the visitors of
+/// fixed-size byte strings, in formats that lack a byte string type.
+///
+pub(crate) fn read_array<'de, A: SeqAccess<'de>, const N: usize>(
+    mut seq: A,
+) -> Result<[u8; N], A::Error> {
+    let mut bytes = [0u8; N];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(index, &"more bytes"))?;
+    }
+    if seq.next_element::<u8>()?.is_some() {
+        return Err(A::Error::invalid_length(N + 1, &"fewer bytes"));
+    }
+    Ok(bytes)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        #[serde(with = "crate::bytes")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let blob = Blob {
+            data: (0..=255).collect(),
+        };
+        let cbor = serde_cbor::to_vec(&blob).unwrap();
+        // map header, key, byte string header, and the bytes themselves
+        assert_eq!(cbor.len(), 1 + 5 + 3 + 256);
+        assert_eq!(serde_cbor::from_slice::<Blob>(&cbor).unwrap(), blob);
+        let json = serde_json::to_string(&blob).unwrap();
+        assert!(json.starts_with("{\"data\":[0,1,2,"));
+        assert_eq!(serde_json::from_str::<Blob>(&json).unwrap(), blob);
+        assert!(serde_json::from_str::<Blob>("{\"data\":[256]}").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines the [`Chunker`] trait, which is implemented by each of
+//! the chunkers in this crate, regardless of the algorithm. This allows code to
+//! be generic over the algorithm, selecting one at runtime if need be, without
+//! having to know about the different [`Chunk`](crate::v2020::Chunk) types of
+//! each module.
+//!
+//! ```no_run
+//! # #[cfg(feature = "alloc")] {
+//! use fastcdc::chunker::Chunker;
+//! use fastcdc::{ronomon, v2020};
+//! use std::convert::Infallible;
+//!
+//! fn total<C: Chunker>(mut chunker: C) -> usize {
+//!     let mut total = 0;
+//!     while let Some(result) = chunker.next_entry() {
+//!         let Ok((info, data)) = result else { break };
+//!         assert_eq!(info.length, data.len());
+//!         total += info.length;
+//!     }
+//!     total
+//! }
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let chunker: Box<dyn Chunker<Error = Infallible>> = if contents.len() > 100_000 {
+//!     Box::new(v2020::FastCDC::new(&contents, 4096, 16384, 65535))
+//! } else {
+//!     Box::new(ronomon::FastCDC::new(&contents, 8192, 16384, 32768))
+//! };
+//! assert_eq!(total(chunker), contents.len());
+//! # }
+//! ```
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+
+///
+/// Describes a chunk found by any of the chunkers, independent of algorithm.
+///
+/// The `hash` is the value of the rolling hash as of the end of the chunk,
+/// which for the [`ronomon`](crate::ronomon) chunker is a 32-bit value widened
+/// to 64 bits.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkInfo {
+    /// The hash value as of the end of the chunk.
+    pub hash: u64,
+    /// Starting byte position within the source.
+    pub offset: u64,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+    /// Why the chunker ended the chunk where it did.
+    pub cut: Cut,
+}
+
+///
+/// The reason that a chunker ended a chunk where it did.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cut {
+    /// The rolling hash matched the mask.
+    Hash,
+    /// The chunk reached the maximum size without the hash matching.
+    MaxSize,
+    /// The data ran out before the maximum size, without the hash matching.
+    End,
+}
+
+impl Cut {
+    ///
+    /// Classify a cut point found by the gear hash of the `v2016` and `v2020`
+    /// modules, given the number of bytes that were available to it.
+    ///
+    /// Those only ever cut on the hash before the last of the bytes given to
+    /// them, or at the maximum size, so a chunk that uses them all was not cut
+    /// by the hash.
+    ///
+    pub(crate) fn of_gear(length: usize, available: usize, max_size: usize) -> Self {
+        if length < available.min(max_size) {
+            Cut::Hash
+        } else if length == max_size {
+            Cut::MaxSize
+        } else {
+            Cut::End
+        }
+    }
+}
+
+///
+/// The result of [`Chunker::next_entry`]: the description of the chunk and the
+/// bytes it contains, or the error that occurred.
+///
+pub type ChunkResult<'a, E> = Result<(ChunkInfo, &'a [u8]), E>;
+
+///
+/// Common interface for all of the chunkers in this crate.
+///
+/// Each call to [`next_entry()`](Chunker::next_entry) yields the description of
+/// the next chunk along with the bytes of that chunk. The slice is borrowed
+/// from the chunker, either from the source given to the in-memory chunkers or
+/// from the internal buffer of the streaming chunkers, and remains valid until
+/// the next call.
+///
+/// The in-memory chunkers never fail, and use [`Infallible`] as their error
+/// type, while the streaming chunkers report I/O errors from their source.
+///
+/// [`Infallible`]: core::convert::Infallible
+///
+pub trait Chunker {
+    /// The type of error that may occur while chunking.
+    type Error;
+
+    /// Find the next chunk, returning `None` once the source is exhausted.
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Self::Error>>;
+}
+
+impl<C: Chunker + ?Sized> Chunker for &mut C {
+    type Error = C::Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Self::Error>> {
+        (**self).next_entry()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: Chunker + ?Sized> Chunker for Box<C> {
+    type Error = C::Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Self::Error>> {
+        (**self).next_entry()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{ronomon, v2016, v2020};
+    use std::fs::{self, File};
+
+    // Drain a chunker into descriptors, checking each slice along the way.
+    fn collect<C: Chunker>(mut chunker: C, contents: &[u8]) -> Vec<ChunkInfo>
+    where
+        C::Error: std::fmt::Debug,
+    {
+        let mut results = Vec::new();
+        while let Some(result) = chunker.next_entry() {
+            let (info, data) = result.unwrap();
+            let start = info.offset as usize;
+            assert_eq!(data, &contents[start..start + info.length]);
+            results.push(info);
+        }
+        results
+    }
+
+    // The hash, offset, and length of each chunk.
+    fn spans(chunks: &[ChunkInfo]) -> Vec<(u64, u64, usize)> {
+        chunks
+            .iter()
+            .map(|c| (c.hash, c.offset, c.length))
+            .collect()
+    }
+
+    #[test]
+    fn test_chunker_matches_iterators() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<(u64, u64, usize)> = v2020::FastCDC::new(&contents, 4096, 16384, 65535)
+            .map(|c| (c.hash, c.offset as u64, c.length))
+            .collect();
+        assert_eq!(expected.len(), 5);
+        let chunker = v2020::FastCDC::new(&contents, 4096, 16384, 65535);
+        let results = collect(chunker, &contents);
+        assert_eq!(spans(&results), expected);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = v2020::StreamCDC::new(file, 4096, 16384, 65535);
+        assert_eq!(collect(chunker, &contents), results);
+
+        let expected: Vec<(u64, u64, usize)> = v2016::FastCDC::new(&contents, 4096, 16384, 65535)
+            .map(|c| (c.hash, c.offset as u64, c.length))
+            .collect();
+        let chunker = v2016::FastCDC::new(&contents, 4096, 16384, 65535);
+        let results = collect(chunker, &contents);
+        assert_eq!(spans(&results), expected);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = v2016::StreamCDC::new(file, 4096, 16384, 65535);
+        assert_eq!(collect(chunker, &contents), results);
+
+        let expected: Vec<(u64, u64, usize)> = ronomon::FastCDC::new(&contents, 8192, 16384, 32768)
+            .map(|c| (c.hash as u64, c.offset as u64, c.length))
+            .collect();
+        assert_eq!(expected.len(), 6);
+        assert_eq!(expected[4].0, 2984739645);
+        let chunker = ronomon::FastCDC::new(&contents, 8192, 16384, 32768);
+        let results = collect(chunker, &contents);
+        assert_eq!(spans(&results), expected);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = ronomon::StreamCDC::new(file, 8192, 16384, 32768);
+        assert_eq!(collect(chunker, &contents), results);
+    }
+
+    #[test]
+    fn test_chunker_cut() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = v2020::FastCDC::new(&contents, 4096, 16384, 65535);
+        let cuts: Vec<Cut> = collect(chunker, &contents).iter().map(|c| c.cut).collect();
+        assert_eq!(cuts, [Cut::Hash, Cut::Hash, Cut::Hash, Cut::Hash, Cut::End]);
+
+        // every chunk of zeros reaches the maximum size, but for the last
+        let zeros = vec![0u8; 10_000];
+        let mut expected = vec![Cut::MaxSize; 9];
+        expected.push(Cut::End);
+        let chunkers: Vec<Box<dyn Chunker<Error = std::convert::Infallible>>> = vec![
+            Box::new(ronomon::FastCDC::new(&zeros, 256, 512, 1024)),
+            Box::new(v2016::FastCDC::new(&zeros, 64, 256, 1024)),
+            Box::new(v2020::FastCDC::new(&zeros, 64, 256, 1024)),
+        ];
+        for chunker in chunkers {
+            let cuts: Vec<Cut> = collect(chunker, &zeros).iter().map(|c| c.cut).collect();
+            assert_eq!(cuts, expected);
+        }
+        let chunker = v2020::StreamCDC::new(zeros.as_slice(), 64, 256, 1024);
+        let cuts: Vec<Cut> = collect(chunker, &zeros).iter().map(|c| c.cut).collect();
+        assert_eq!(cuts, expected);
+        let chunker = v2020::LendingStreamCDC::new(zeros.as_slice(), 64, 256, 1024);
+        let cuts: Vec<Cut> = collect(chunker, &zeros).iter().map(|c| c.cut).collect();
+        assert_eq!(cuts, expected);
+        let chunker = ronomon::StreamCDC::new(zeros.as_slice(), 256, 512, 1024);
+        let cuts: Vec<Cut> = collect(chunker, &zeros).iter().map(|c| c.cut).collect();
+        assert_eq!(cuts, expected);
+    }
+
+    #[test]
+    fn test_chunker_dyn() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunkers: Vec<Box<dyn Chunker<Error = std::convert::Infallible>>> = vec![
+            Box::new(ronomon::FastCDC::new(&contents, 8192, 16384, 32768)),
+            Box::new(v2016::FastCDC::new(&contents, 4096, 16384, 65535)),
+            Box::new(v2020::FastCDC::new(&contents, 4096, 16384, 65535)),
+        ];
+        for chunker in chunkers {
+            let chunks = collect(chunker, &contents);
+            let total: usize = chunks.iter().map(|c| c.length).sum();
+            assert_eq!(total, contents.len());
+        }
+    }
+
+    #[test]
+    fn test_stream_chunker_mixed_with_iterator() {
+        // Alternating the trait and the iterator must not lose any bytes.
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunker = v2020::StreamCDC::new(contents.as_slice(), 4096, 16384, 65535);
+        let (first, _) = chunker.next_entry().unwrap().unwrap();
+        let second = chunker.next().unwrap().unwrap();
+        assert_eq!(second.offset, first.length as u64);
+        let (third, data) = chunker.next_entry().unwrap().unwrap();
+        assert_eq!(third.offset, second.offset + second.length as u64);
+        assert_eq!(data.len(), third.length);
+    }
+}
@@ -0,0 +1,512 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`ChunkerConfig`], a validated set of chunking
+//! parameters that can be given to the `with_config()` constructor of any of
+//! the chunkers in this crate.
+//!
+//! The constructors that take the sizes directly only check the bounds with
+//! `debug_assert!`, so in a release build an invalid value silently produces
+//! unexpected results (or panics deep inside the chunker). When the parameters
+//! come from somewhere other than the source code, such as a configuration
+//! file, use [`ChunkerConfigBuilder::try_build()`] to reject them cleanly.
+//!
+//! ```
+//! use fastcdc::config::{ChunkerConfig, ConfigError, Normalization};
+//! use fastcdc::v2020::FastCDC;
+//!
+//! let config = ChunkerConfig::builder(4096, 16384, 65535)
+//!     .normalization(Normalization::Level2)
+//!     .try_build()
+//!     .unwrap();
+//! let contents = vec![0u8; 100_000];
+//! let chunker = FastCDC::with_config(&contents, &config).unwrap();
+//! assert_eq!(chunker.count(), 2);
+//!
+//! let result = ChunkerConfig::builder(65536, 16384, 4096).try_build();
+//! assert!(matches!(result, Err(ConfigError::SizesOutOfOrder { .. })));
+//! ```
+use crate::fingerprint::Fingerprint;
+use crate::v2020::{AVERAGE_MAX, AVERAGE_MIN, MAXIMUM_MAX, MAXIMUM_MIN, MINIMUM_MAX, MINIMUM_MIN};
+use core::fmt;
+
+///
+/// Identifies one of the chunking algorithms implemented by this crate, each of
+/// which produces different cut points for the same parameters.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Algorithm {
+    /// The [`ronomon`](crate::ronomon) variation of FastCDC.
+    Ronomon,
+    /// The canonical 2016 algorithm in the [`v2016`](crate::v2016) module.
+    V2016,
+    /// The canonical 2020 algorithm in the [`v2020`](crate::v2020) module.
+    V2020,
+}
+
+impl Algorithm {
+    /// The name of the module that implements the algorithm.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Ronomon => "ronomon",
+            Algorithm::V2016 => "v2016",
+            Algorithm::V2020 => "v2020",
+        }
+    }
+
+    ///
+    /// The revision of the implementation of this algorithm.
+    ///
+    /// The revision is incremented whenever a release of this crate changes the
+    /// cut points or hash values produced for the same input and parameters,
+    /// such that chunks produced by different revisions should not be mixed.
+    ///
+    pub fn revision(&self) -> u32 {
+        match self {
+            Algorithm::Ronomon => 1,
+            Algorithm::V2016 => 1,
+            Algorithm::V2020 => 1,
+        }
+    }
+
+    ///
+    /// Check that the implementation of this algorithm supports all of the
+    /// settings in the given configuration.
+    ///
+    pub fn check(&self, config: &ChunkerConfig) -> Result<(), ConfigError> {
+        match self {
+            Algorithm::Ronomon => crate::ronomon::check_config(config),
+            Algorithm::V2016 | Algorithm::V2020 => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+///
+/// The level for the normalized chunking used by FastCDC.
+///
+/// Normalized chunking "generates chunks whose sizes are normalized to a
+/// specified region centered at the expected chunk size," as described in
+/// section 4.4 of the FastCDC 2016 paper.
+///
+/// Note that lower levels of normalization will result in a larger range of
+/// generated chunk sizes. It may be beneficial to widen the minimum/maximum
+/// chunk size values given to the `FastCDC` constructor in that case.
+///
+/// Note that higher levels of normalization may result in the final chunk of
+/// data being smaller than the minimum chunk size, which results in a hash
+/// value of zero since no calculations are performed for sub-minimum chunks.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Normalization {
+    /// No chunk size normalization, produces a wide range of chunk sizes.
+    Level0,
+    /// Level 1 normalization, in which fewer chunks are outside of the desired range.
+    Level1,
+    /// Level 2 normalization, where most chunks are of the desired size.
+    Level2,
+    /// Level 3 normalization, nearly all chunks are the desired size.
+    Level3,
+}
+
+impl Normalization {
+    /// Convert the level to a numeric value (Level0 -> 0, Level1 -> 1, etc).
+    pub fn bits(&self) -> u32 {
+        match self {
+            Normalization::Level0 => 0,
+            Normalization::Level1 => 1,
+            Normalization::Level2 => 2,
+            Normalization::Level3 => 3,
+        }
+    }
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.bits().fmt(f)
+    }
+}
+
+///
+/// The error type returned when chunking parameters are rejected.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ConfigError {
+    /// The minimum chunk size is outside of `MINIMUM_MIN..=MINIMUM_MAX`.
+    MinimumOutOfRange(usize),
+    /// The average chunk size is outside of `AVERAGE_MIN..=AVERAGE_MAX`.
+    AverageOutOfRange(usize),
+    /// The maximum chunk size is outside of `MAXIMUM_MIN..=MAXIMUM_MAX`.
+    MaximumOutOfRange(usize),
+    /// The sizes do not satisfy `min_size <= avg_size <= max_size`.
+    SizesOutOfOrder {
+        /// The minimum chunk size.
+        min_size: usize,
+        /// The average chunk size.
+        avg_size: usize,
+        /// The maximum chunk size.
+        max_size: usize,
+    },
+    /// The chunker does not support the given setting.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MinimumOutOfRange(v) => write!(
+                f,
+                "minimum chunk size {v} not in range {MINIMUM_MIN}..={MINIMUM_MAX}"
+            ),
+            ConfigError::AverageOutOfRange(v) => write!(
+                f,
+                "average chunk size {v} not in range {AVERAGE_MIN}..={AVERAGE_MAX}"
+            ),
+            ConfigError::MaximumOutOfRange(v) => write!(
+                f,
+                "maximum chunk size {v} not in range {MAXIMUM_MIN}..={MAXIMUM_MAX}"
+            ),
+            ConfigError::SizesOutOfOrder {
+                min_size,
+                avg_size,
+                max_size,
+            } => write!(
+                f,
+                "chunk sizes must be ordered min <= avg <= max: {min_size}, {avg_size}, {max_size}"
+            ),
+            ConfigError::Unsupported(s) => write!(f, "unsupported: {s}"),
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+///
+/// A validated set of chunking parameters.
+///
+/// Use [`ChunkerConfig::builder()`] to construct an instance, then pass it to
+/// the `with_config()` constructor of a chunker. Because the sizes are checked
+/// when the configuration is built, a `ChunkerConfig` is always within the
+/// bounds accepted by the [`v2016`](crate::v2016) and [`v2020`](crate::v2020)
+/// chunkers, whose `with_config()` constructors never fail and return a
+/// `Result` only for consistency with the others. The
+/// [`ronomon`](crate::ronomon) chunker accepts the same sizes, but only
+/// supports [`Normalization::Level1`] and a seed of zero.
+///
+/// The sizes accepted by the `ronomon` chunker extend beyond those of the other
+/// chunkers (for instance, an average size of up to
+/// [`ronomon::AVERAGE_MAX`](crate::ronomon::AVERAGE_MAX)), but a configuration
+/// is limited to the range common to all of them. Use the constructors that
+/// take the sizes directly for the larger sizes.
+///
+/// With the `serde` feature enabled, a configuration is validated as it is
+/// deserialized, in which case the normalization level and seed are optional.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ChunkerConfigBuilder"))]
+pub struct ChunkerConfig {
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    normalization: Normalization,
+    seed: u64,
+}
+
+impl ChunkerConfig {
+    ///
+    /// Start building a configuration with the given chunk sizes, using
+    /// normalization level 1 and a seed of zero by default.
+    ///
+    pub fn builder(min_size: usize, avg_size: usize, max_size: usize) -> ChunkerConfigBuilder {
+        ChunkerConfigBuilder {
+            min_size,
+            avg_size,
+            max_size,
+            normalization: Normalization::Level1,
+            seed: 0,
+        }
+    }
+
+    /// The minimum chunk size.
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    /// The desired average chunk size.
+    pub fn avg_size(&self) -> usize {
+        self.avg_size
+    }
+
+    /// The maximum chunk size.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// The level of chunk size normalization.
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// The seed used to alter the gear hash (zero means unaltered).
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    ///
+    /// Produce the [`Fingerprint`] that identifies the chunks produced by the
+    /// given algorithm with this configuration.
+    ///
+    /// Returns `ConfigError::Unsupported` if the algorithm cannot honor this
+    /// configuration.
+    ///
+    pub fn fingerprint(&self, algorithm: Algorithm) -> Result<Fingerprint, ConfigError> {
+        Fingerprint::new(algorithm, self)
+    }
+}
+
+///
+/// Builder for [`ChunkerConfig`], created by [`ChunkerConfig::builder()`].
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct ChunkerConfigBuilder {
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    #[cfg_attr(feature = "serde", serde(default = "default_normalization"))]
+    normalization: Normalization,
+    #[cfg_attr(feature = "serde", serde(default))]
+    seed: u64,
+}
+
+#[cfg(feature = "serde")]
+fn default_normalization() -> Normalization {
+    Normalization::Level1
+}
+
+impl ChunkerConfigBuilder {
+    /// Set the level of chunk size normalization.
+    pub fn normalization(mut self, level: Normalization) -> Self {
+        self.normalization = level;
+        self
+    }
+
+    /// Set the seed to be XOR'd with the values in the gear tables.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    ///
+    /// Validate the parameters and produce the configuration.
+    ///
+    /// Each size must be within the acceptable range for that size of the
+    /// `v2016` and `v2020` chunkers (e.g. [`MINIMUM_MIN`] to [`MINIMUM_MAX`]
+    /// for the minimum), even if the configuration is meant for the `ronomon`
+    /// chunker, and the sizes must satisfy `min_size <= avg_size <= max_size`.
+    ///
+    pub fn try_build(self) -> Result<ChunkerConfig, ConfigError> {
+        if !(MINIMUM_MIN..=MINIMUM_MAX).contains(&self.min_size) {
+            return Err(ConfigError::MinimumOutOfRange(self.min_size));
+        }
+        if !(AVERAGE_MIN..=AVERAGE_MAX).contains(&self.avg_size) {
+            return Err(ConfigError::AverageOutOfRange(self.avg_size));
+        }
+        if !(MAXIMUM_MIN..=MAXIMUM_MAX).contains(&self.max_size) {
+            return Err(ConfigError::MaximumOutOfRange(self.max_size));
+        }
+        if self.min_size > self.avg_size || self.avg_size > self.max_size {
+            return Err(ConfigError::SizesOutOfOrder {
+                min_size: self.min_size,
+                avg_size: self.avg_size,
+                max_size: self.max_size,
+            });
+        }
+        Ok(ChunkerConfig {
+            min_size: self.min_size,
+            avg_size: self.avg_size,
+            max_size: self.max_size,
+            normalization: self.normalization,
+            seed: self.seed,
+        })
+    }
+}
+
+impl TryFrom<ChunkerConfigBuilder> for ChunkerConfig {
+    type Error = ConfigError;
+
+    fn try_from(builder: ChunkerConfigBuilder) -> Result<Self, ConfigError> {
+        builder.try_build()
+    }
+}
+
+// Rounded base-2 logarithm; matches the behavior pre-4.0.0 so that mask
+// selection picks the bucket whose target chunk size is closest to `value`,
+// rather than always rounding down (which `usize::ilog2` does). The result is
+// rounded up when `value` is at least `2^(k + 0.5)`, where `k` is the rounded
+// down logarithm, which is determined by squaring both sides, using only
+// integer arithmetic.
+pub(crate) fn logarithm2(value: usize) -> u32 {
+    if value == 0 {
+        return 0;
+    }
+    let k = value.ilog2();
+    if (value as u128).pow(2) >= 1_u128 << (2 * k + 1) {
+        k + 1
+    } else {
+        k
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_defaults() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        assert_eq!(config.min_size(), 4096);
+        assert_eq!(config.avg_size(), 16384);
+        assert_eq!(config.max_size(), 65535);
+        assert_eq!(config.normalization(), Normalization::Level1);
+        assert_eq!(config.seed(), 0);
+    }
+
+    #[test]
+    fn test_build_all_settings() {
+        let config = ChunkerConfig::builder(8192, 16384, 32768)
+            .normalization(Normalization::Level3)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        assert_eq!(config.normalization(), Normalization::Level3);
+        assert_eq!(config.seed(), 666);
+    }
+
+    #[test]
+    fn test_build_limits() {
+        // the extremes of every range are acceptable
+        assert!(ChunkerConfig::builder(64, 256, 1024).try_build().is_ok());
+        assert!(
+            ChunkerConfig::builder(1_048_576, 4_194_304, 16_777_216)
+                .normalization(Normalization::Level3)
+                .try_build()
+                .is_ok()
+        );
+        // min == avg == max is odd, but not invalid
+        assert!(ChunkerConfig::builder(4096, 4096, 4096).try_build().is_ok());
+    }
+
+    #[test]
+    fn test_build_out_of_range() {
+        let result = ChunkerConfig::builder(63, 256, 1024).try_build();
+        assert_eq!(result, Err(ConfigError::MinimumOutOfRange(63)));
+        let result = ChunkerConfig::builder(2_000_000, 4_000_000, 8_000_000).try_build();
+        assert_eq!(result, Err(ConfigError::MinimumOutOfRange(2_000_000)));
+        let result = ChunkerConfig::builder(64, 255, 1024).try_build();
+        assert_eq!(result, Err(ConfigError::AverageOutOfRange(255)));
+        let result = ChunkerConfig::builder(64, 268_435_457, 1024).try_build();
+        assert_eq!(result, Err(ConfigError::AverageOutOfRange(268_435_457)));
+        let result = ChunkerConfig::builder(64, 256, 1023).try_build();
+        assert_eq!(result, Err(ConfigError::MaximumOutOfRange(1023)));
+        let result = ChunkerConfig::builder(64, 256, 16_777_217).try_build();
+        assert_eq!(result, Err(ConfigError::MaximumOutOfRange(16_777_217)));
+        // valid for ronomon, but not for the other chunkers
+        let result = ChunkerConfig::builder(8192, 8_388_608, 16_777_216).try_build();
+        assert_eq!(result, Err(ConfigError::AverageOutOfRange(8_388_608)));
+    }
+
+    #[test]
+    fn test_build_out_of_order() {
+        let result = ChunkerConfig::builder(32768, 16384, 65536).try_build();
+        assert_eq!(
+            result,
+            Err(ConfigError::SizesOutOfOrder {
+                min_size: 32768,
+                avg_size: 16384,
+                max_size: 65536
+            })
+        );
+        let result = ChunkerConfig::builder(4096, 65536, 16384).try_build();
+        assert!(matches!(result, Err(ConfigError::SizesOutOfOrder { .. })));
+    }
+
+    #[test]
+    fn test_error_fmt() {
+        let err = ConfigError::MinimumOutOfRange(63);
+        assert_eq!(
+            format!("{err}"),
+            "minimum chunk size 63 not in range 64..=1048576"
+        );
+        let err = ConfigError::SizesOutOfOrder {
+            min_size: 3,
+            avg_size: 2,
+            max_size: 1,
+        };
+        assert_eq!(
+            format!("{err}"),
+            "chunk sizes must be ordered min <= avg <= max: 3, 2, 1"
+        );
+    }
+
+    #[test]
+    fn test_logarithm2_matches_float() {
+        // the masks chosen before the integer version must be unchanged
+        let float = |value: usize| (value as f64).log2().round() as u32;
+        for value in 0..=(1 << 20) {
+            assert_eq!(logarithm2(value), float(value), "{value}");
+        }
+        for bits in 20..40 {
+            // either side of the rounding point between 2^bits and 2^(bits+1)
+            let middle = (2.0_f64.powf(bits as f64 + 0.5)) as usize;
+            for value in middle - 1000..middle + 1000 {
+                assert_eq!(logarithm2(value), float(value), "{value}");
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_config() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            json,
+            r#"{"min_size":4096,"avg_size":16384,"max_size":65535,"normalization":"Level2","seed":666}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ChunkerConfig>(&json).unwrap(),
+            config
+        );
+        // the normalization and seed are optional
+        let config: ChunkerConfig =
+            serde_json::from_str(r#"{"min_size":64,"avg_size":256,"max_size":1024}"#).unwrap();
+        assert_eq!(
+            config,
+            ChunkerConfig::builder(64, 256, 1024).try_build().unwrap()
+        );
+        // the sizes are validated
+        let result = serde_json::from_str::<ChunkerConfig>(
+            r#"{"min_size":8,"avg_size":256,"max_size":1024}"#,
+        );
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains(&ConfigError::MinimumOutOfRange(8).to_string()));
+        let algorithm: Algorithm = serde_json::from_str(r#""V2016""#).unwrap();
+        assert_eq!(algorithm, Algorithm::V2016);
+    }
+}
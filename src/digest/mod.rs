@@ -0,0 +1,405 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`ChunkHasher`], the trait by which the chunkers compute
+//! a content digest of each chunk, and the adapters that pair each chunk with
+//! its digest.
+//!
+//! The digest is not computed during the search for the cut point, which only
+//! scans the bytes after the minimum chunk size, a pair at a time, while a hash
+//! function consumes all of the bytes of the chunk, typically a block at a
+//! time. Instead, the bytes of each chunk are hashed in a separate pass as soon
+//! as its cut point is found, while they are likely still in the processor's
+//! cache, and before the next chunk is read. The chunk types themselves have no
+//! digest field, such that they remain the same whether or not a digest is
+//! computed.
+//!
+//! The trait is implemented for `()`, which computes nothing, for
+//! `blake3::Hasher` when the `blake3` feature is enabled, and for
+//! `sha2::Sha256` and `sha2::Sha512` when the `sha2` feature is enabled,
+//! each producing its digest as an array of bytes. Any other hash function can
+//! be used by implementing the trait.
+//!
+//! ```
+//! use fastcdc::digest::ChunkHasher;
+//! use fastcdc::v2020::FastCDC;
+//! use std::hash::{DefaultHasher, Hasher};
+//!
+//! // the hasher of the standard library, for the sake of the example
+//! #[derive(Default)]
+//! struct Std(DefaultHasher);
+//!
+//! impl ChunkHasher for Std {
+//!     type Digest = u64;
+//!
+//!     fn update(&mut self, data: &[u8]) {
+//!         self.0.write(data);
+//!     }
+//!
+//!     fn finalize_reset(&mut self) -> u64 {
+//!         std::mem::take(&mut self.0).finish()
+//!     }
+//! }
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+//! for (chunk, digest) in chunker.with_hasher(Std::default()) {
+//!     println!("offset={} length={} digest={:016x}", chunk.offset, chunk.length, digest);
+//! }
+//! ```
+//!
+//! The in-memory chunkers return a [`DigestedCDC`] from their `with_hasher()`
+//! methods, which hashes each chunk from the source. The streaming chunkers
+//! produce each chunk with a copy of its bytes, and the [`Digested`] adapter
+//! returned by their `with_hasher()` methods hashes that copy as soon as it is
+//! made.
+use core::ops::Range;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use core::pin::Pin;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use core::task::{Context, Poll};
+#[cfg(feature = "futures")]
+use futures::stream::Stream;
+#[cfg(all(feature = "tokio", not(feature = "futures")))]
+use tokio_stream::Stream;
+
+///
+/// A hash function that computes the digest of each chunk, one chunk at a
+/// time, reusing the same state for every chunk.
+///
+pub trait ChunkHasher {
+    /// The type of the digest of a chunk.
+    type Digest;
+
+    /// Add the given bytes of the current chunk to the hash.
+    fn update(&mut self, data: &[u8]);
+
+    /// Return the digest of the current chunk, and reset the state in
+    /// preparation for the next chunk.
+    fn finalize_reset(&mut self) -> Self::Digest;
+
+    ///
+    /// Return the digest of the given chunk, which is the sole input to the
+    /// hash, leaving the state reset for the next chunk.
+    ///
+    fn digest(&mut self, data: &[u8]) -> Self::Digest {
+        self.update(data);
+        self.finalize_reset()
+    }
+}
+
+///
+/// Computes nothing, for chunkers that do not produce a digest.
+///
+impl ChunkHasher for () {
+    type Digest = ();
+
+    fn update(&mut self, _data: &[u8]) {}
+
+    fn finalize_reset(&mut self) {}
+}
+
+///
+/// Iterator over the chunks of an in-memory `FastCDC` and their digests,
+/// created by the `with_hasher()` method of the `FastCDC` of each module.
+///
+/// Each chunk is hashed from the source once its cut point has been found.
+///
+#[derive(Debug, Clone)]
+pub struct DigestedCDC<'a, C: Iterator, H> {
+    chunker: C,
+    source: &'a [u8],
+    hasher: H,
+    // the range of the source covered by a chunk
+    span: fn(&C::Item) -> Range<usize>,
+}
+
+impl<'a, C: Iterator, H> DigestedCDC<'a, C, H> {
+    /// Pair the chunks of the chunker over `source` with their digests.
+    pub(crate) fn new(
+        chunker: C,
+        source: &'a [u8],
+        hasher: H,
+        span: fn(&C::Item) -> Range<usize>,
+    ) -> Self {
+        Self {
+            chunker,
+            source,
+            hasher,
+            span,
+        }
+    }
+
+    ///
+    /// Return the chunker and the hasher, such as to continue chunking without
+    /// producing digests.
+    ///
+    pub fn into_inner(self) -> (C, H) {
+        (self.chunker, self.hasher)
+    }
+}
+
+impl<C: Iterator, H: ChunkHasher> Iterator for DigestedCDC<'_, C, H> {
+    type Item = (C::Item, H::Digest);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunker.next()?;
+        let digest = self.hasher.digest(&self.source[(self.span)(&chunk)]);
+        Some((chunk, digest))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunker.size_hint()
+    }
+}
+
+///
+/// Adapter that pairs each chunk produced by a streaming chunker with its
+/// digest, created by the `with_hasher()` method of the `StreamCDC` of each
+/// module, or with [`Digested::new()`] for any iterator or stream of results
+/// whose chunks are `AsRef<[u8]>`, such as the `ChunkData` of each module.
+///
+/// With the `tokio` or `futures` feature, this is also a `Stream` when the
+/// inner stream is `Unpin`, such that the streams of the `AsyncStreamCDC` of
+/// each module can be digested once they are pinned.
+///
+/// ```
+/// # #[cfg(all(feature = "futures", feature = "blake3"))]
+/// # futures::executor::block_on(async {
+/// use fastcdc::digest::Digested;
+/// use fastcdc::v2020::AsyncStreamCDC;
+/// use futures::stream::StreamExt;
+///
+/// let source = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let mut chunker = AsyncStreamCDC::new(source.as_slice(), 4096, 16384, 65535);
+/// let stream = std::pin::pin!(chunker.as_futures_stream());
+/// let mut digested = Digested::new(stream, blake3::Hasher::new());
+/// while let Some(result) = digested.next().await {
+///     let (chunk, digest) = result.unwrap();
+///     assert_eq!(&digest, blake3::hash(&chunk.data).as_bytes());
+/// }
+/// # });
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Digested<I, H> {
+    inner: I,
+    hasher: H,
+}
+
+impl<I, H> Digested<I, H> {
+    ///
+    /// Compute the digest of each chunk produced by `inner` with the given
+    /// hasher.
+    ///
+    pub fn new(inner: I, hasher: H) -> Self {
+        Self { inner, hasher }
+    }
+
+    ///
+    /// Return the chunker and the hasher, such as to continue chunking without
+    /// producing digests.
+    ///
+    pub fn into_inner(self) -> (I, H) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<I, H, C, E> Iterator for Digested<I, H>
+where
+    I: Iterator<Item = Result<C, E>>,
+    H: ChunkHasher,
+    C: AsRef<[u8]>,
+{
+    type Item = Result<(C, H::Digest), E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.inner.next()?;
+        Some(chunk.map(|chunk| {
+            let digest = self.hasher.digest(chunk.as_ref());
+            (chunk, digest)
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl<S, H, C, E> Stream for Digested<S, H>
+where
+    S: Stream<Item = Result<C, E>> + Unpin,
+    H: ChunkHasher + Unpin,
+    C: AsRef<[u8]>,
+{
+    type Item = Result<(C, H::Digest), E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.inner).poll_next(cx).map(|chunk| {
+            chunk.map(|chunk| {
+                chunk.map(|chunk| {
+                    let digest = this.hasher.digest(chunk.as_ref());
+                    (chunk, digest)
+                })
+            })
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl ChunkHasher for blake3::Hasher {
+    type Digest = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 32] {
+        let hash = self.finalize();
+        self.reset();
+        hash.into()
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl ChunkHasher for sha2::Sha256 {
+    type Digest = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(self, data);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 32] {
+        sha2::Digest::finalize_reset(self).into()
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl ChunkHasher for sha2::Sha512 {
+    type Digest = [u8; 64];
+
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(self, data);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 64] {
+        sha2::Digest::finalize_reset(self).into()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::v2020::{Chunk, FastCDC};
+    use std::fs;
+
+    // Hashes each chunk to the sum of its bytes, counting the calls.
+    #[derive(Default)]
+    struct Summer {
+        sum: u64,
+        chunks: usize,
+    }
+
+    impl ChunkHasher for Summer {
+        type Digest = u64;
+
+        fn update(&mut self, data: &[u8]) {
+            self.sum += data.iter().map(|b| *b as u64).sum::<u64>();
+        }
+
+        fn finalize_reset(&mut self) -> u64 {
+            self.chunks += 1;
+            core::mem::take(&mut self.sum)
+        }
+    }
+
+    #[test]
+    fn test_unit_hasher() {
+        let mut hasher = ();
+        hasher.update(b"ignored");
+        assert_eq!(hasher.digest(b"abc"), ());
+    }
+
+    #[test]
+    fn test_digested_cdc() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let expected: Vec<Chunk> = chunker.clone().collect();
+        let mut digested = chunker.with_hasher(Summer::default());
+        let mut results = Vec::new();
+        results.extend(digested.by_ref());
+        assert_eq!(results.len(), expected.len());
+        for ((chunk, digest), other) in results.iter().zip(&expected) {
+            assert_eq!(chunk, other);
+            let data = &contents[chunk.offset..chunk.offset + chunk.length];
+            assert_eq!(*digest, data.iter().map(|b| *b as u64).sum::<u64>());
+        }
+        let (mut chunker, hasher) = digested.into_inner();
+        assert_eq!(hasher.chunks, expected.len());
+        assert!(chunker.next().is_none());
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_digested() {
+        let chunks: [Result<&[u8], &str>; 3] = [Ok(b"abc"), Err("failed"), Ok(b"")];
+        let mut digested = Digested::new(chunks.into_iter(), blake3::Hasher::new());
+        assert_eq!(
+            digested.next(),
+            Some(Ok((&b"abc"[..], *blake3::hash(b"abc").as_bytes())))
+        );
+        assert_eq!(digested.next(), Some(Err("failed")));
+        assert_eq!(
+            digested.next(),
+            Some(Ok((&b""[..], *blake3::hash(b"").as_bytes())))
+        );
+        assert_eq!(digested.next(), None);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_hasher() {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"partial chunk");
+        assert_eq!(
+            hasher.finalize_reset(),
+            *blake3::hash(b"partial chunk").as_bytes()
+        );
+        ChunkHasher::update(&mut hasher, b"ab");
+        ChunkHasher::update(&mut hasher, b"c");
+        assert_eq!(hasher.finalize_reset(), *blake3::hash(b"abc").as_bytes());
+        assert_eq!(hasher.digest(b""), *blake3::hash(b"").as_bytes());
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha2_hashers() {
+        use sha2::Digest;
+        let mut hasher = sha2::Sha256::new();
+        ChunkHasher::update(&mut hasher, b"ignored");
+        ChunkHasher::finalize_reset(&mut hasher);
+        let digest = ChunkHasher::digest(&mut hasher, b"abc");
+        assert_eq!(
+            digest,
+            <[u8; 32]>::from(<sha2::Sha256 as Digest>::digest(b"abc"))
+        );
+        assert_eq!(
+            digest[..4],
+            [0xba, 0x78, 0x16, 0xbf],
+            "SHA-256 of \"abc\" from FIPS 180-2"
+        );
+        let mut hasher = sha2::Sha512::new();
+        let digest = ChunkHasher::digest(&mut hasher, b"abc");
+        assert_eq!(
+            digest,
+            <[u8; 64]>::from(<sha2::Sha512 as Digest>::digest(b"abc"))
+        );
+    }
+}
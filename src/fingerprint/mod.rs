@@ -0,0 +1,704 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`Fingerprint`], a stable description of everything that
+//! determines the cut points produced by a chunker: the algorithm and the
+//! revision of its implementation, the chunk sizes, the normalization level,
+//! the seed, and the identity of the gear table.
+//!
+//! Changing any of these parameters changes the chunks produced for the same
+//! data, which defeats deduplication against chunks that were stored earlier.
+//! An application that persists chunks can store the fingerprint alongside
+//! them, and later compare it with the fingerprint of the current
+//! configuration, refusing to mix chunks produced by incompatible chunkers.
+//!
+//! A fingerprint can be serialized to a fixed-size byte array with
+//! [`Fingerprint::to_bytes()`], or to a line of text via `Display`, and read
+//! back with [`Fingerprint::from_bytes()`] or `FromStr`, respectively.
+//!
+//! ```
+//! use fastcdc::config::{Algorithm, ChunkerConfig};
+//! use fastcdc::fingerprint::Fingerprint;
+//!
+//! let config = ChunkerConfig::builder(4096, 16384, 65535).try_build().unwrap();
+//! let fingerprint = config.fingerprint(Algorithm::V2020).unwrap();
+//! let stored = fingerprint.to_string();
+//! // ...later, when adding chunks to the same repository...
+//! let previous: Fingerprint = stored.parse().unwrap();
+//! assert_eq!(previous, config.fingerprint(Algorithm::V2020).unwrap());
+//! assert_ne!(previous, config.fingerprint(Algorithm::V2016).unwrap());
+//! ```
+use crate::config::{Algorithm, ChunkerConfig, ConfigError, Normalization};
+use crate::gear::GearTable;
+use core::fmt;
+use core::str::FromStr;
+
+/// Version of the serialized format produced by this release of the crate.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Length in bytes of the output of [`Fingerprint::to_bytes()`].
+pub const ENCODED_LEN: usize = 52;
+
+// Leading bytes of the binary encoding.
+const MAGIC: [u8; 4] = *b"FCFP";
+
+// Leading text of the string encoding, minus the version number.
+const PREFIX: &str = "fcfp";
+
+///
+/// The error type returned when a serialized fingerprint cannot be decoded.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FingerprintError {
+    /// The input does not have the expected length.
+    InvalidLength(usize),
+    /// The input does not start with the expected magic bytes or prefix.
+    InvalidMagic,
+    /// The input was produced by a newer, unsupported format version.
+    UnsupportedVersion(u8),
+    /// The algorithm is not one of those implemented by this crate.
+    UnknownAlgorithm,
+    /// The normalization level is not a known level.
+    InvalidNormalization,
+    /// A field of the text encoding could not be parsed.
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FingerprintError::InvalidLength(n) => {
+                write!(f, "fingerprint must be {ENCODED_LEN} bytes, not {n}")
+            }
+            FingerprintError::InvalidMagic => write!(f, "input is not a fingerprint"),
+            FingerprintError::UnsupportedVersion(v) => {
+                write!(f, "unsupported fingerprint format version {v}")
+            }
+            FingerprintError::UnknownAlgorithm => write!(f, "unknown chunking algorithm"),
+            FingerprintError::InvalidNormalization => write!(f, "invalid normalization level"),
+            FingerprintError::InvalidField(name) => write!(f, "invalid fingerprint field: {name}"),
+        }
+    }
+}
+
+impl core::error::Error for FingerprintError {}
+
+///
+/// Describes the parameters that determine the cut points produced by a
+/// chunker, such that two chunkers with equal fingerprints produce identical
+/// chunks for the same data.
+///
+/// Equality is the test for compatibility: a difference in any field means the
+/// chunks are likely to differ. This includes the revision of the algorithm,
+/// which changes when a release of this crate changes the cut points.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Fingerprint {
+    algorithm: Algorithm,
+    revision: u32,
+    min_size: u64,
+    avg_size: u64,
+    max_size: u64,
+    normalization: Normalization,
+    seed: u64,
+    gear_id: u64,
+}
+
+impl Fingerprint {
+    ///
+    /// Produce the fingerprint for the given algorithm and configuration.
+    ///
+    /// Returns `ConfigError::Unsupported` if the algorithm cannot honor the
+    /// configuration.
+    ///
+    pub fn new(algorithm: Algorithm, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        algorithm.check(config)?;
+        Ok(Self {
+            algorithm,
+            revision: algorithm.revision(),
+            min_size: config.min_size() as u64,
+            avg_size: config.avg_size() as u64,
+            max_size: config.max_size() as u64,
+            normalization: config.normalization(),
+            seed: config.seed(),
+            gear_id: gear_table_id(algorithm, config.seed()),
+        })
+    }
+
+    ///
+    /// Replace the identity of the gear table with that of the given table,
+    /// for a chunker constructed with `with_level_and_gear()`, or with
+    /// `with_level_and_key()`, whose table is [`GearTable::from_key()`].
+    ///
+    /// Returns `ConfigError::Unsupported` for the `ronomon` algorithm, which
+    /// always uses its built-in table. Note that [`Fingerprint::config()`]
+    /// cannot reproduce the table, which must be kept by the application.
+    ///
+    /// ```
+    /// use fastcdc::config::{Algorithm, ChunkerConfig};
+    /// use fastcdc::fingerprint::Fingerprint;
+    /// use fastcdc::gear::GearTable;
+    ///
+    /// let config = ChunkerConfig::builder(4096, 16384, 65535).try_build().unwrap();
+    /// let plain = Fingerprint::new(Algorithm::V2020, &config).unwrap();
+    /// let table = GearTable::from_key(&[7; 16]);
+    /// let keyed = plain.with_gear(&table).unwrap();
+    /// assert_ne!(plain, keyed);
+    /// assert_eq!(keyed.gear_id(), table.id());
+    /// ```
+    ///
+    pub fn with_gear(self, table: &GearTable) -> Result<Self, ConfigError> {
+        if self.algorithm == Algorithm::Ronomon {
+            return Err(ConfigError::Unsupported(
+                "ronomon does not support custom gear tables",
+            ));
+        }
+        Ok(Self {
+            gear_id: table.id(),
+            ..self
+        })
+    }
+
+    /// The chunking algorithm.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The revision of the algorithm implementation.
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// The minimum chunk size.
+    pub fn min_size(&self) -> u64 {
+        self.min_size
+    }
+
+    /// The desired average chunk size.
+    pub fn avg_size(&self) -> u64 {
+        self.avg_size
+    }
+
+    /// The maximum chunk size.
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// The level of chunk size normalization.
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// The seed used to alter the gear hash.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    ///
+    /// The identity of the gear table, a 64-bit FNV-1a hash of the table values
+    /// (after applying the seed, if any), or the [`GearTable::id()`] of the
+    /// table given to [`Fingerprint::with_gear()`].
+    ///
+    pub fn gear_id(&self) -> u64 {
+        self.gear_id
+    }
+
+    ///
+    /// Reconstruct the configuration described by this fingerprint, for
+    /// instance to continue chunking with the parameters used previously.
+    ///
+    /// Note that the resulting configuration will produce a different
+    /// fingerprint if this one came from a different revision of the crate.
+    ///
+    pub fn config(&self) -> Result<ChunkerConfig, ConfigError> {
+        let min_size = usize::try_from(self.min_size).unwrap_or(usize::MAX);
+        let avg_size = usize::try_from(self.avg_size).unwrap_or(usize::MAX);
+        let max_size = usize::try_from(self.max_size).unwrap_or(usize::MAX);
+        let config = ChunkerConfig::builder(min_size, avg_size, max_size)
+            .normalization(self.normalization)
+            .seed(self.seed)
+            .try_build()?;
+        self.algorithm.check(&config)?;
+        Ok(config)
+    }
+
+    ///
+    /// Encode the fingerprint as a fixed-size array of bytes.
+    ///
+    /// The layout is the magic bytes `FCFP`, the format version, algorithm,
+    /// and normalization level (one byte each), a reserved zero byte, the
+    /// revision as a little-endian `u32`, followed by the minimum, average, and
+    /// maximum sizes, the seed, and the gear table identity, each as a
+    /// little-endian `u64`.
+    ///
+    pub fn to_bytes(&self) -> [u8; ENCODED_LEN] {
+        let mut bytes = [0u8; ENCODED_LEN];
+        bytes[0..4].copy_from_slice(&MAGIC);
+        bytes[4] = FORMAT_VERSION;
+        bytes[5] = algorithm_code(self.algorithm);
+        bytes[6] = self.normalization.bits() as u8;
+        bytes[8..12].copy_from_slice(&self.revision.to_le_bytes());
+        let fields = [
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.seed,
+            self.gear_id,
+        ];
+        for (index, value) in fields.iter().enumerate() {
+            let start = 12 + index * 8;
+            bytes[start..start + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///
+    /// Decode a fingerprint produced by [`Fingerprint::to_bytes()`].
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FingerprintError> {
+        if bytes.len() != ENCODED_LEN {
+            return Err(FingerprintError::InvalidLength(bytes.len()));
+        }
+        if bytes[0..4] != MAGIC {
+            return Err(FingerprintError::InvalidMagic);
+        }
+        if bytes[4] == 0 || bytes[4] > FORMAT_VERSION {
+            return Err(FingerprintError::UnsupportedVersion(bytes[4]));
+        }
+        let algorithm = algorithm_from_code(bytes[5])?;
+        let normalization = normalization_from_bits(bytes[6] as u32)?;
+        let revision = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        let field = |index: usize| {
+            let start = 12 + index * 8;
+            u64::from_le_bytes(bytes[start..start + 8].try_into().unwrap())
+        };
+        Ok(Self {
+            algorithm,
+            revision,
+            min_size: field(0),
+            avg_size: field(1),
+            max_size: field(2),
+            normalization,
+            seed: field(3),
+            gear_id: field(4),
+        })
+    }
+}
+
+///
+/// Formats the fingerprint as a single line of text, such as
+/// `fcfp1:v2020:r1:4096:16384:65535:n1:s0000000000000000:ge30aaa9dd8e0f632`,
+/// which can be parsed with `FromStr`.
+///
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{PREFIX}{FORMAT_VERSION}:{}:r{}:{}:{}:{}:n{}:s{:016x}:g{:016x}",
+            self.algorithm,
+            self.revision,
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.normalization,
+            self.seed,
+            self.gear_id
+        )
+    }
+}
+
+///
+/// Serializes the fingerprint as a line of text in human-readable formats,
+/// such as JSON, and as the output of [`Fingerprint::to_bytes()`] otherwise.
+///
+#[cfg(feature = "serde")]
+impl serde::Serialize for Fingerprint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Fingerprint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FingerprintVisitor)
+        } else {
+            deserializer.deserialize_bytes(FingerprintVisitor)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct FingerprintVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for FingerprintVisitor {
+    type Value = Fingerprint;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a chunker fingerprint")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Fingerprint, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<Fingerprint, E> {
+        Fingerprint::from_bytes(value).map_err(E::custom)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Fingerprint, A::Error> {
+        let bytes: [u8; ENCODED_LEN] = crate::bytes::read_array(seq)?;
+        self.visit_bytes(&bytes)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = FingerprintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = [""; 9];
+        let mut count = 0;
+        for field in s.trim().split(':') {
+            if let Some(slot) = fields.get_mut(count) {
+                *slot = field;
+            }
+            count += 1;
+        }
+        let version = fields[0]
+            .strip_prefix(PREFIX)
+            .ok_or(FingerprintError::InvalidMagic)?
+            .parse::<u8>()
+            .map_err(|_| FingerprintError::InvalidField("version"))?;
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(FingerprintError::UnsupportedVersion(version));
+        }
+        if count != fields.len() {
+            return Err(FingerprintError::InvalidField("count"));
+        }
+        let algorithm = match fields[1] {
+            "ronomon" => Algorithm::Ronomon,
+            "v2016" => Algorithm::V2016,
+            "v2020" => Algorithm::V2020,
+            _ => return Err(FingerprintError::UnknownAlgorithm),
+        };
+        let tagged = |index: usize, tag: char, name: &'static str| {
+            fields[index]
+                .strip_prefix(tag)
+                .ok_or(FingerprintError::InvalidField(name))
+        };
+        let revision = tagged(2, 'r', "revision")?
+            .parse::<u32>()
+            .map_err(|_| FingerprintError::InvalidField("revision"))?;
+        let size = |index: usize, name: &'static str| {
+            fields[index]
+                .parse::<u64>()
+                .map_err(|_| FingerprintError::InvalidField(name))
+        };
+        let level = tagged(6, 'n', "normalization")?
+            .parse::<u32>()
+            .map_err(|_| FingerprintError::InvalidNormalization)?;
+        let hex = |index: usize, tag: char, name: &'static str| {
+            u64::from_str_radix(tagged(index, tag, name)?, 16)
+                .map_err(|_| FingerprintError::InvalidField(name))
+        };
+        Ok(Self {
+            algorithm,
+            revision,
+            min_size: size(3, "min_size")?,
+            avg_size: size(4, "avg_size")?,
+            max_size: size(5, "max_size")?,
+            normalization: normalization_from_bits(level)?,
+            seed: hex(7, 's', "seed")?,
+            gear_id: hex(8, 'g', "gear")?,
+        })
+    }
+}
+
+fn algorithm_code(algorithm: Algorithm) -> u8 {
+    match algorithm {
+        Algorithm::Ronomon => 1,
+        Algorithm::V2016 => 2,
+        Algorithm::V2020 => 3,
+    }
+}
+
+fn algorithm_from_code(code: u8) -> Result<Algorithm, FingerprintError> {
+    match code {
+        1 => Ok(Algorithm::Ronomon),
+        2 => Ok(Algorithm::V2016),
+        3 => Ok(Algorithm::V2020),
+        _ => Err(FingerprintError::UnknownAlgorithm),
+    }
+}
+
+fn normalization_from_bits(bits: u32) -> Result<Normalization, FingerprintError> {
+    match bits {
+        0 => Ok(Normalization::Level0),
+        1 => Ok(Normalization::Level1),
+        2 => Ok(Normalization::Level2),
+        3 => Ok(Normalization::Level3),
+        _ => Err(FingerprintError::InvalidNormalization),
+    }
+}
+
+///
+/// Compute the identity of the gear table used by the algorithm with the given
+/// seed, as the 64-bit FNV-1a hash of the little-endian bytes of the table.
+///
+fn gear_table_id(algorithm: Algorithm, seed: u64) -> u64 {
+    match algorithm {
+        Algorithm::Ronomon => fnv1a(crate::ronomon::TABLE.iter().flat_map(|v| v.to_le_bytes())),
+        Algorithm::V2016 => {
+            let gear = crate::v2016::gear_with_seed(seed);
+            fnv1a(gear.iter().flat_map(|v| v.to_le_bytes()))
+        }
+        Algorithm::V2020 => {
+            let (gear, _) = crate::v2020::gear_with_seed(seed);
+            fnv1a(gear.iter().flat_map(|v| v.to_le_bytes()))
+        }
+    }
+}
+
+pub(crate) fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn default_config() -> ChunkerConfig {
+        ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        // test vectors from the FNV reference
+        assert_eq!(fnv1a("".bytes()), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a".bytes()), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("foobar".bytes()), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_fingerprint_stable() {
+        // If this test fails, the cut points have probably changed as well,
+        // and the revision of the algorithm must be incremented.
+        let fingerprint = Fingerprint::new(Algorithm::V2020, &default_config()).unwrap();
+        assert_eq!(
+            fingerprint.to_string(),
+            "fcfp1:v2020:r1:4096:16384:65535:n1:s0000000000000000:ge30aaa9dd8e0f632"
+        );
+        let fingerprint = Fingerprint::new(Algorithm::V2016, &default_config()).unwrap();
+        assert_eq!(fingerprint.gear_id(), 0xe30aaa9dd8e0f632);
+        let fingerprint = Fingerprint::new(Algorithm::Ronomon, &default_config()).unwrap();
+        assert_eq!(fingerprint.gear_id(), 0x569a5328ce982d6c);
+    }
+
+    #[test]
+    fn test_fingerprint_differs() {
+        let config = default_config();
+        let base = config.fingerprint(Algorithm::V2020).unwrap();
+        assert_ne!(base, config.fingerprint(Algorithm::V2016).unwrap());
+        let seeded = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(666)
+            .try_build()
+            .unwrap()
+            .fingerprint(Algorithm::V2020)
+            .unwrap();
+        assert_ne!(base, seeded);
+        assert_ne!(base.gear_id(), seeded.gear_id());
+        let seeded_2016 = seeded
+            .config()
+            .unwrap()
+            .fingerprint(Algorithm::V2016)
+            .unwrap();
+        assert_eq!(seeded.gear_id(), seeded_2016.gear_id());
+        let level2 = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .try_build()
+            .unwrap()
+            .fingerprint(Algorithm::V2020)
+            .unwrap();
+        assert_ne!(base, level2);
+        assert_eq!(base.gear_id(), level2.gear_id());
+    }
+
+    #[test]
+    fn test_fingerprint_gear() {
+        let config = default_config();
+        let plain = Fingerprint::new(Algorithm::V2020, &config).unwrap();
+        // the built-in table has the same identity
+        assert_eq!(plain.with_gear(&GearTable::md5()).unwrap(), plain);
+        let keyed = plain.with_gear(&GearTable::from_key(&[1; 16])).unwrap();
+        assert_ne!(keyed, plain);
+        let other = plain.with_gear(&GearTable::from_key(&[2; 16])).unwrap();
+        assert_ne!(other, keyed);
+        let custom = plain.with_gear(&GearTable::from_splitmix(42)).unwrap();
+        assert_ne!(custom, plain);
+        assert_eq!(custom.gear_id(), GearTable::from_splitmix(42).id());
+        assert_eq!(Fingerprint::from_bytes(&custom.to_bytes()), Ok(custom));
+        let v2016 = Fingerprint::new(Algorithm::V2016, &config).unwrap();
+        assert_ne!(
+            v2016.with_gear(&GearTable::from_key(&[1; 16])).unwrap(),
+            v2016
+        );
+        let ronomon = Fingerprint::new(Algorithm::Ronomon, &config).unwrap();
+        assert!(ronomon.with_gear(&GearTable::md5()).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_unsupported() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .try_build()
+            .unwrap();
+        let result = config.fingerprint(Algorithm::Ronomon);
+        assert!(matches!(result, Err(ConfigError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let config = ChunkerConfig::builder(8192, 16384, 32768)
+            .normalization(Normalization::Level3)
+            .seed(0xdead_beef)
+            .try_build()
+            .unwrap();
+        for algorithm in [Algorithm::Ronomon, Algorithm::V2016, Algorithm::V2020] {
+            let Ok(fingerprint) = config.fingerprint(algorithm) else {
+                continue;
+            };
+            let bytes = fingerprint.to_bytes();
+            assert_eq!(&bytes[0..4], b"FCFP");
+            assert_eq!(Fingerprint::from_bytes(&bytes).unwrap(), fingerprint);
+            assert_eq!(fingerprint.config().unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        let config = ChunkerConfig::builder(8192, 16384, 32768)
+            .normalization(Normalization::Level0)
+            .seed(u64::MAX)
+            .try_build()
+            .unwrap();
+        let fingerprint = config.fingerprint(Algorithm::V2020).unwrap();
+        let text = fingerprint.to_string();
+        assert_eq!(text.parse::<Fingerprint>().unwrap(), fingerprint);
+        let fingerprint = default_config().fingerprint(Algorithm::Ronomon).unwrap();
+        let text = format!("{fingerprint}\n");
+        assert_eq!(text.parse::<Fingerprint>().unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        let bytes = default_config()
+            .fingerprint(Algorithm::V2020)
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            Fingerprint::from_bytes(&bytes[..51]),
+            Err(FingerprintError::InvalidLength(51))
+        );
+        let mut bad = bytes;
+        bad[0] = b'X';
+        assert_eq!(
+            Fingerprint::from_bytes(&bad),
+            Err(FingerprintError::InvalidMagic)
+        );
+        let mut bad = bytes;
+        bad[4] = 2;
+        assert_eq!(
+            Fingerprint::from_bytes(&bad),
+            Err(FingerprintError::UnsupportedVersion(2))
+        );
+        let mut bad = bytes;
+        bad[5] = 9;
+        assert_eq!(
+            Fingerprint::from_bytes(&bad),
+            Err(FingerprintError::UnknownAlgorithm)
+        );
+        let mut bad = bytes;
+        bad[6] = 4;
+        assert_eq!(
+            Fingerprint::from_bytes(&bad),
+            Err(FingerprintError::InvalidNormalization)
+        );
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        let parse = |s: &str| s.parse::<Fingerprint>();
+        assert_eq!(parse(""), Err(FingerprintError::InvalidMagic));
+        assert_eq!(
+            parse("fcfp2:v2020"),
+            Err(FingerprintError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            parse("fcfp1:v2020:r1"),
+            Err(FingerprintError::InvalidField("count"))
+        );
+        assert_eq!(
+            parse("fcfp1:v2021:r1:4096:16384:65535:n1:s0:g0"),
+            Err(FingerprintError::UnknownAlgorithm)
+        );
+        assert_eq!(
+            parse("fcfp1:v2020:1:4096:16384:65535:n1:s0:g0"),
+            Err(FingerprintError::InvalidField("revision"))
+        );
+        assert_eq!(
+            parse("fcfp1:v2020:r1:4k:16384:65535:n1:s0:g0"),
+            Err(FingerprintError::InvalidField("min_size"))
+        );
+        assert_eq!(
+            parse("fcfp1:v2020:r1:4096:16384:65535:n5:s0:g0"),
+            Err(FingerprintError::InvalidNormalization)
+        );
+        assert_eq!(
+            parse("fcfp1:v2020:r1:4096:16384:65535:n1:s0:gx"),
+            Err(FingerprintError::InvalidField("gear"))
+        );
+    }
+
+    #[test]
+    fn test_error_fmt() {
+        let err = FingerprintError::InvalidLength(3);
+        assert_eq!(format!("{err}"), "fingerprint must be 52 bytes, not 3");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_fingerprint() {
+        let fingerprint = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(42)
+            .try_build()
+            .unwrap()
+            .fingerprint(Algorithm::V2016)
+            .unwrap();
+        // text in human-readable formats
+        let json = serde_json::to_string(&fingerprint).unwrap();
+        assert_eq!(json, format!("\"{fingerprint}\""));
+        assert_eq!(
+            serde_json::from_str::<Fingerprint>(&json).unwrap(),
+            fingerprint
+        );
+        // bytes in binary formats
+        let cbor = serde_cbor::to_vec(&fingerprint).unwrap();
+        assert_eq!(cbor[2..], fingerprint.to_bytes());
+        assert_eq!(
+            serde_cbor::from_slice::<Fingerprint>(&cbor).unwrap(),
+            fingerprint
+        );
+        assert!(serde_json::from_str::<Fingerprint>("\"fcfp9:v2020\"").is_err());
+    }
+}
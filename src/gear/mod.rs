@@ -0,0 +1,409 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`GearTable`], the table of 256 values used by the gear
+//! hash of the [`v2016`](crate::v2016) and [`v2020`](crate::v2020) chunkers,
+//! which can be given to the `with_level_and_gear()` constructors of those
+//! chunkers in place of the built-in table.
+//!
+//! A table can be generated with the recipe of the reference implementation
+//! of FastCDC, which takes the MD5 digest of each index repeated 64 times, or
+//! from any seed or secret key. A table can also be saved with
+//! [`GearTable::to_bytes()`] and loaded with [`GearTable::from_bytes()`],
+//! allowing for pinning the table used by a repository, or reproducing the
+//! table of another implementation.
+//!
+//! ```
+//! use fastcdc::gear::GearTable;
+//! use fastcdc::v2020::{FastCDC, Normalization};
+//!
+//! let table = GearTable::from_splitmix(42);
+//! let bytes = table.to_bytes();
+//! // ...later, perhaps in another process...
+//! let loaded = GearTable::from_bytes(&bytes).unwrap();
+//! assert_eq!(loaded, table);
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let chunker =
+//!     FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, Normalization::Level1, &loaded);
+//! assert_eq!(chunker.map(|c| c.length).sum::<usize>(), contents.len());
+//! ```
+use crate::fingerprint::fnv1a;
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, boxed::Box};
+use core::fmt;
+use core::ops::Deref;
+
+mod md5;
+
+/// Length in bytes of the serialized form of a [`GearTable`].
+pub const ENCODED_LEN: usize = 2048;
+
+///
+/// The error type returned when a gear table is rejected.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GearTableError {
+    /// The input does not have the expected length.
+    InvalidLength(usize),
+    /// The entry at the given index has the same value as an earlier entry,
+    /// hence the gear hash cannot distinguish the two bytes.
+    DuplicateEntry(usize),
+    /// The given bit has the same value in every entry, which weakens the
+    /// corresponding bits of the gear hash.
+    ConstantBit(u32),
+}
+
+impl fmt::Display for GearTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GearTableError::InvalidLength(n) => {
+                write!(f, "gear table must be {ENCODED_LEN} bytes, not {n}")
+            }
+            GearTableError::DuplicateEntry(i) => write!(f, "gear table entry {i} is a duplicate"),
+            GearTableError::ConstantBit(b) => write!(f, "gear table bit {b} is constant"),
+        }
+    }
+}
+
+impl core::error::Error for GearTableError {}
+
+///
+/// A validated table of 256 values for the gear hash, one for each byte value.
+///
+/// The table used by the `v2020` chunkers for the left-shifted hash judgement
+/// is derived from this table, as with the built-in tables.
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GearTable {
+    values: [u64; 256],
+}
+
+impl GearTable {
+    ///
+    /// Create a table from the given values, returning an error if the values
+    /// are not suitable for a gear hash.
+    ///
+    /// The values are rejected if any two are equal, or if any bit has the
+    /// same value in all of the entries.
+    ///
+    pub fn new(values: [u64; 256]) -> Result<Self, GearTableError> {
+        let mut sorted = values;
+        sorted.sort_unstable();
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            let index = values.iter().rposition(|v| *v == pair[0]).unwrap();
+            return Err(GearTableError::DuplicateEntry(index));
+        }
+        let any_set = values.iter().fold(0, |acc, v| acc | v);
+        let all_set = values.iter().fold(u64::MAX, |acc, v| acc & v);
+        let constant = !any_set | all_set;
+        if constant != 0 {
+            return Err(GearTableError::ConstantBit(constant.trailing_zeros()));
+        }
+        Ok(Self { values })
+    }
+
+    ///
+    /// Generate the table using the recipe of the reference implementation,
+    /// in which each value is the first 8 bytes (big-endian) of the MD5 digest
+    /// of 64 bytes filled with the index of the entry. This is the built-in
+    /// table of the `v2016` and `v2020` chunkers.
+    ///
+    pub fn md5() -> Self {
+        Self::generated(core::array::from_fn(|index| {
+            let digest = md5::md5(&[index as u8; 64]);
+            u64::from_be_bytes(digest[..8].try_into().unwrap())
+        }))
+    }
+
+    ///
+    /// Generate a table from the given seed, using the SplitMix64 generator.
+    ///
+    /// The same seed always produces the same table. Unlike the seed given to
+    /// the `with_level_and_seed()` constructors, which alters the values of
+    /// the built-in table, the values are entirely determined by the seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the generated values are rejected by [`GearTable::new()`],
+    /// which no seed is known to produce.
+    ///
+    pub fn from_splitmix(seed: u64) -> Self {
+        let mut state = seed;
+        Self::generated(core::array::from_fn(|_| {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }))
+    }
+
+    ///
+    /// Derive a table from the given secret key, identical to the table
+    /// produced by [`get_gear_with_key()`](crate::v2020::get_gear_with_key).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the derived values are rejected by [`GearTable::new()`],
+    /// which no key is known to produce.
+    ///
+    pub fn from_key(key: &[u8; 16]) -> Self {
+        let (gear, _) = crate::v2020::gear_with_key(key);
+        Self::generated(core::array::from_fn(|index| gear[index]))
+    }
+
+    ///
+    /// Load a table from the byte form produced by [`GearTable::to_bytes()`],
+    /// validating the values as with [`GearTable::new()`].
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GearTableError> {
+        if bytes.len() != ENCODED_LEN {
+            return Err(GearTableError::InvalidLength(bytes.len()));
+        }
+        let values = core::array::from_fn(|index| {
+            u64::from_le_bytes(bytes[index * 8..index * 8 + 8].try_into().unwrap())
+        });
+        Self::new(values)
+    }
+
+    ///
+    /// Serialize the table as the little-endian bytes of each value, in order.
+    ///
+    pub fn to_bytes(&self) -> [u8; ENCODED_LEN] {
+        let mut bytes = [0u8; ENCODED_LEN];
+        for (index, value) in self.values.iter().enumerate() {
+            bytes[index * 8..index * 8 + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///
+    /// The values of the table, indexed by byte value.
+    ///
+    pub fn values(&self) -> &[u64; 256] {
+        &self.values
+    }
+
+    ///
+    /// The identity of the table, a 64-bit FNV-1a hash of the bytes produced by
+    /// [`GearTable::to_bytes()`], as recorded in a fingerprint by
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn id(&self) -> u64 {
+        fnv1a(self.values.iter().flat_map(|v| v.to_le_bytes()))
+    }
+
+    // Validate the values of a generated table, which are expected to pass.
+    fn generated(values: [u64; 256]) -> Self {
+        Self::new(values).expect("generated gear table should be valid")
+    }
+
+    /// Produce the table and its left-shifted twin for use by a chunker.
+    pub(crate) fn tables(&self) -> (Table, Table) {
+        let gear_ls = self.values.map(|v| v << 1);
+        (Table::generated(self.values), Table::generated(gear_ls))
+    }
+}
+
+///
+/// Serializes the table as the output of [`GearTable::to_bytes()`], and checks
+/// the values when deserializing, as with [`GearTable::from_bytes()`].
+///
+#[cfg(feature = "serde")]
+impl serde::Serialize for GearTable {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GearTable {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(GearTableVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct GearTableVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for GearTableVisitor {
+    type Value = GearTable;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a gear table of {ENCODED_LEN} bytes")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<GearTable, E> {
+        GearTable::from_bytes(value).map_err(E::custom)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<GearTable, A::Error> {
+        let bytes: [u8; ENCODED_LEN] = crate::bytes::read_array(seq)?;
+        self.visit_bytes(&bytes)
+    }
+}
+
+impl Default for GearTable {
+    ///
+    /// The built-in table of the `v2016` and `v2020` chunkers.
+    ///
+    fn default() -> Self {
+        Self {
+            values: crate::v2020::GEAR,
+        }
+    }
+}
+
+// Storage for a generated table, which is kept on the heap when possible, to
+// keep the size of the chunkers small.
+#[cfg(feature = "alloc")]
+type Storage = Box<[u64; 256]>;
+#[cfg(not(feature = "alloc"))]
+type Storage = [u64; 256];
+
+///
+/// A gear table as held by a chunker, either one of the built-in tables or a
+/// table generated from a seed, key, or [`GearTable`].
+///
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(not(feature = "alloc"), allow(clippy::large_enum_variant))]
+pub(crate) enum Table {
+    Builtin(&'static [u64; 256]),
+    Generated(Storage),
+}
+
+impl Table {
+    // the conversion boxes the table only when `alloc` is enabled
+    #[allow(clippy::useless_conversion)]
+    pub(crate) fn generated(values: [u64; 256]) -> Self {
+        Table::Generated(values.into())
+    }
+
+    /// Convert to the form returned by the public `get_gear_*` functions.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn into_cow(self) -> Cow<'static, [u64]> {
+        match self {
+            Table::Builtin(values) => Cow::Borrowed(values),
+            Table::Generated(values) => Cow::Owned(values.to_vec()),
+        }
+    }
+}
+
+impl Deref for Table {
+    type Target = [u64];
+
+    fn deref(&self) -> &[u64] {
+        match self {
+            Table::Builtin(values) => &values[..],
+            Table::Generated(values) => &values[..],
+        }
+    }
+}
+
+impl PartialEq for Table {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::config::{Algorithm, ChunkerConfig};
+
+    #[test]
+    fn test_md5_matches_builtin() {
+        assert_eq!(GearTable::md5(), GearTable::default());
+        let gear = crate::v2016::get_gear_with_seed(0);
+        assert_eq!(&GearTable::md5().values()[..], &gear[..]);
+    }
+
+    #[test]
+    fn test_builtin_tables_valid() {
+        let table = GearTable::default();
+        assert_eq!(GearTable::new(*table.values()), Ok(table));
+        let table = GearTable::from_key(&[0u8; 16]);
+        assert_eq!(GearTable::new(*table.values()), Ok(table));
+        for seed in [0, 1, 42, u64::MAX] {
+            let table = GearTable::from_splitmix(seed);
+            assert_eq!(GearTable::new(*table.values()), Ok(table));
+        }
+    }
+
+    #[test]
+    fn test_from_splitmix_stable() {
+        // If this test fails, every chunker using a seeded table has changed.
+        let table = GearTable::from_splitmix(0);
+        assert_eq!(table.values()[0], 0xe220a8397b1dcdaf);
+        assert_eq!(table.values()[1], 0x6e789e6aa1b965f4);
+        assert_ne!(GearTable::from_splitmix(1), table);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let table = GearTable::from_splitmix(42);
+        let bytes = table.to_bytes();
+        assert_eq!(&bytes[..8], &table.values()[0].to_le_bytes());
+        assert_eq!(GearTable::from_bytes(&bytes), Ok(table));
+        assert_eq!(
+            GearTable::from_bytes(&bytes[1..]),
+            Err(GearTableError::InvalidLength(2047))
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid() {
+        let mut values = *GearTable::default().values();
+        values[200] = values[10];
+        assert_eq!(
+            GearTable::new(values),
+            Err(GearTableError::DuplicateEntry(200))
+        );
+        let values = GearTable::default().values().map(|v| v | 0x10);
+        assert_eq!(GearTable::new(values), Err(GearTableError::ConstantBit(4)));
+        let values = GearTable::default().values().map(|v| v << 1);
+        assert_eq!(GearTable::new(values), Err(GearTableError::ConstantBit(0)));
+        let mut bytes = GearTable::default().to_bytes();
+        bytes.copy_within(..8, 8);
+        assert_eq!(
+            GearTable::from_bytes(&bytes),
+            Err(GearTableError::DuplicateEntry(1))
+        );
+    }
+
+    #[test]
+    fn test_id_matches_fingerprint() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        let fingerprint = config.fingerprint(Algorithm::V2020).unwrap();
+        assert_eq!(GearTable::default().id(), fingerprint.gear_id());
+    }
+
+    #[test]
+    fn test_error_fmt() {
+        let err = GearTableError::InvalidLength(10);
+        assert_eq!(format!("{err}"), "gear table must be 2048 bytes, not 10");
+        let err = GearTableError::ConstantBit(4);
+        assert_eq!(format!("{err}"), "gear table bit 4 is constant");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_gear_table() {
+        let table = GearTable::from_splitmix(42);
+        let cbor = serde_cbor::to_vec(&table).unwrap();
+        assert_eq!(cbor.len(), 3 + ENCODED_LEN);
+        assert_eq!(serde_cbor::from_slice::<GearTable>(&cbor).unwrap(), table);
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(serde_json::from_str::<GearTable>(&json).unwrap(), table);
+        // the values are checked
+        let zeros = serde_cbor::to_vec(&serde_cbor::Value::Bytes(vec![0; ENCODED_LEN])).unwrap();
+        assert!(serde_cbor::from_slice::<GearTable>(&zeros).is_err());
+        assert!(serde_json::from_str::<GearTable>("[1,2,3]").is_err());
+    }
+}
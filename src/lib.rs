@@ -113,16 +113,143 @@
 //! Ideally you want cut points that are determined by the input data. However,
 //! this is application dependent and your situation may be different.
 //!
+//! ## Validated Configuration
+//!
+//! The constructors that take the chunk sizes directly only check them with
+//! `debug_assert!`, which is compiled out of release builds. If the sizes come
+//! from outside of the program, such as a configuration file, use
+//! [`config::ChunkerConfig`] to validate them and report any problems as an
+//! error, then pass the configuration to the `with_config()` constructor of the
+//! chosen chunker.
+//!
+//! The configuration can also produce a [`fingerprint::Fingerprint`] that
+//! identifies the chunking algorithm, the revision of its implementation, and
+//! all of the parameters that affect the cut points. Storing the fingerprint
+//! alongside the chunks makes it possible to detect, years later, that the
+//! chunker has changed and new chunks will not deduplicate against old ones.
+//!
+//! The chunks of each file can be saved in the binary format of the
+//! [`manifest`] module, whose header records the fingerprint, and read back
+//! later, one entry at a time.
+//!
+//! With the `serde` feature enabled, the configuration, fingerprint, chunk,
+//! and manifest types implement `Serialize` and `Deserialize`, and a
+//! [`config::ChunkerConfig`] is validated as it is deserialized.
+//!
+//! ## Custom Gear Tables
+//!
+//! The [`v2016`] and [`v2020`] chunkers can use a [`gear::GearTable`] in place
+//! of the built-in gear table. A table can be generated in the same manner as
+//! the built-in table, from a seed, or from a secret key, and can be saved and
+//! loaded in a fixed 2 KiB byte form.
+//!
+//! ## Generic Chunking
+//!
+//! Each module defines its own `Chunk` type, but all of the chunkers also
+//! implement the [`chunker::Chunker`] trait, which yields a common
+//! [`chunker::ChunkInfo`] along with the bytes of each chunk, and the reason
+//! for the cut. This allows for selecting the algorithm at runtime, or writing
+//! code that is generic over the algorithm.
+//!
+//! ## Chunk Digests
+//!
+//! The chunkers can compute a content digest of each chunk right after it is
+//! found, with any hash function that implements [`digest::ChunkHasher`], via
+//! the `with_hasher()` method of the `FastCDC` and `StreamCDC` of each module,
+//! or by wrapping the streams of an `AsyncStreamCDC` in a
+//! [`digest::Digested`]. The `blake3` and `sha2` features provide
+//! implementations for the hashers of those crates.
+//!
+//! The [`merkle`] module builds a Merkle tree over the digests of the chunks
+//! of a file, whose root identifies the file, and whose proofs verify any one
+//! chunk against that root.
+//!
+//! For very large files, [`v2020::HashSplitter`] groups the digests of the
+//! chunks into a tree of index nodes, found by content-defined chunking of the
+//! list of digests, so that a change to the file changes only a few of the
+//! index nodes rather than the whole list.
+//!
+//! ## Deduplication Statistics
+//!
+//! To compare chunking parameters on a sample of a dataset,
+//! [`stats::DedupStats`] records the chunks of any of the chunkers along with
+//! their digests, and reports the total and unique bytes, the deduplication
+//! ratio, and the distribution of the chunk lengths. Requires `std`.
+//!
+//! Rather than choosing the chunk sizes by hand, [`tuner::Tuner`] runs the
+//! [`v2020`] chunker over a grid of average sizes, minimum and maximum ratios,
+//! and normalization levels, reporting the dedup ratio, metadata overhead, and
+//! throughput of each, and recommending a configuration for a target average
+//! size. The `tune` example does the same from the command line.
+//!
+//! To measure how well the chunk boundaries withstand changes to the data,
+//! [`resilience::Analyzer`] applies synthetic insertions, deletions,
+//! replacements, and block moves at random offsets, chunks the result with
+//! the [`ronomon`], [`v2016`], or [`v2020`] chunker, and reports the chunks and
+//! bytes that would be stored anew after each edit.
+//!
+//! ## Many Buffers
+//!
+//! When chunking many buffers in memory, such as the contents of many small
+//! files, [`v2020::chunk_many()`] scans several of them at once in the same
+//! loop, with results identical to chunking each buffer with
+//! [`v2020::FastCDC`], but at a higher throughput per thread.
+//!
 //! ## Large Data
 //!
 //! If processing very large files, the streaming version of the chunkers in the
-//! [`v2016`] and [`v2020`] modules may be a suitable approach. They both allocate a
-//! byte vector equal to the maximum chunk size, draining and resizing the
-//! vector as chunks are found. However, using a crate such as `memmap2` can be
-//! significantly faster than the streaming chunkers. See the examples in the
-//! `examples` directory for how to use the streaming versions as-is, versus the
-//! non-streaming chunkers which read from a memory-mapped file.
+//! [`ronomon`], [`v2016`], and [`v2020`] modules may be a suitable approach.
+//! They allocate a byte vector equal to the maximum chunk size, draining and
+//! resizing the vector as chunks are found. However, using a crate such as
+//! `memmap2` can be significantly faster than the streaming chunkers. See the
+//! examples in the `examples` directory for how to use the streaming versions
+//! as-is, versus the non-streaming chunkers which read from a memory-mapped
+//! file.
+//!
+//! With the `parallel` feature enabled, `v2020::FastCDC::par_collect()` finds
+//! the chunks of a large slice using all of the threads of the `rayon` thread
+//! pool, with exactly the same result as collecting the chunks sequentially.
+//!
+//! ## no_std
+//!
+//! The `std` feature is enabled by default. Without it, the crate is `no_std`,
+//! and the in-memory `FastCDC` chunkers, `cut()` and `cut_gear()` functions,
+//! gear tables, and configuration remain available; the streaming chunkers and
+//! their error types require `std`. The `alloc` feature, implied by `std`,
+//! adds the functions that return a `Vec` or `Cow`, such as
+//! [`v2020::chunk_many()`] and `collect_cut_points()`.
+//!
+//! ```toml
+//! fastcdc = { version = "4", default-features = false }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub mod async_source;
+#[cfg(feature = "serde")]
+mod bytes;
+pub mod chunker;
+pub mod config;
+pub mod digest;
+pub mod fingerprint;
+pub mod gear;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "alloc")]
+pub mod merkle;
+#[cfg(feature = "std")]
+pub mod resilience;
 pub mod ronomon;
+mod siphash;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(all(test, feature = "std"))]
+mod test_util;
+#[cfg(feature = "std")]
+pub mod tuner;
 pub mod v2016;
 pub mod v2020;
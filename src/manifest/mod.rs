@@ -0,0 +1,535 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines a compact binary format for the list of chunks of a
+//! file, called a manifest, along with [`ManifestWriter`] and
+//! [`ManifestReader`] for writing it to a [`Write`] and reading it back from a
+//! [`Read`], one entry at a time.
+//!
+//! The manifest starts with a header that records the [`Fingerprint`] of the
+//! chunker, that is, the algorithm, chunk sizes, normalization level, and seed,
+//! as well as the length of the content digest stored with each chunk. Each
+//! chunk is then recorded by its length, gear hash, and digest, the offset
+//! being implied by the lengths of the chunks that precede it. A trailer with
+//! the number of chunks and the total length allows the reader to detect a
+//! truncated manifest.
+//!
+//! All integers are stored in little-endian byte order, so a manifest can be
+//! read on any platform.
+//!
+//! ```
+//! use fastcdc::config::{Algorithm, ChunkerConfig};
+//! use fastcdc::manifest::{ManifestEntry, ManifestReader, ManifestWriter};
+//! use fastcdc::v2020::FastCDC;
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let config = ChunkerConfig::builder(4096, 16384, 65535).try_build().unwrap();
+//! let fingerprint = config.fingerprint(Algorithm::V2020).unwrap();
+//! let mut writer = ManifestWriter::new(Vec::new(), &fingerprint, 0).unwrap();
+//! for chunk in FastCDC::with_config(&contents, &config).unwrap() {
+//!     let entry = ManifestEntry::new(chunk.hash, chunk.offset as u64, chunk.length);
+//!     writer.write_entry(&entry).unwrap();
+//! }
+//! let manifest = writer.finish().unwrap();
+//!
+//! let reader = ManifestReader::new(manifest.as_slice()).unwrap();
+//! assert_eq!(reader.fingerprint(), &fingerprint);
+//! let entries: Vec<ManifestEntry> = reader.collect::<Result<_, _>>().unwrap();
+//! assert_eq!(entries.len(), 5);
+//! ```
+use crate::chunker::ChunkInfo;
+use crate::fingerprint::{self, Fingerprint, FingerprintError};
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Version of the manifest format produced by this release of the crate.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Length in bytes of the manifest header.
+pub const HEADER_LEN: usize = 8 + fingerprint::ENCODED_LEN;
+
+/// Largest supported length of the digest of each chunk.
+pub const MAX_DIGEST_LEN: usize = 255;
+
+// Leading bytes of every manifest.
+const MAGIC: [u8; 4] = *b"FCMF";
+
+// Length of an entry without its digest: chunk length and gear hash.
+pub(crate) const ENTRY_LEN: usize = 12;
+
+///
+/// The error type returned when writing or reading a manifest.
+///
+#[derive(Debug)]
+pub enum ManifestError {
+    /// An I/O error occurred.
+    IoError(io::Error),
+    /// The input does not start with the manifest magic bytes.
+    InvalidMagic,
+    /// The input was produced by a newer, unsupported format version.
+    UnsupportedVersion(u8),
+    /// The fingerprint in the header could not be decoded.
+    InvalidFingerprint(FingerprintError),
+    /// The digest does not have the length given in the header.
+    InvalidDigestLength(usize),
+    /// The chunk is empty, or longer than 4 GiB.
+    InvalidLength(usize),
+    /// The chunk does not start where the previous chunk ended.
+    InvalidOffset(u64),
+    /// The input ended before the trailer.
+    Truncated,
+    /// The trailer does not match the entries that precede it.
+    InvalidTrailer,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::IoError(e) => write!(f, "I/O error: {e}"),
+            ManifestError::InvalidMagic => write!(f, "input is not a manifest"),
+            ManifestError::UnsupportedVersion(v) => {
+                write!(f, "unsupported manifest format version {v}")
+            }
+            ManifestError::InvalidFingerprint(e) => write!(f, "invalid manifest header: {e}"),
+            ManifestError::InvalidDigestLength(n) => write!(f, "invalid digest length {n}"),
+            ManifestError::InvalidLength(n) => write!(f, "invalid chunk length {n}"),
+            ManifestError::InvalidOffset(n) => write!(f, "chunk offset {n} is not contiguous"),
+            ManifestError::Truncated => write!(f, "manifest is truncated"),
+            ManifestError::InvalidTrailer => write!(f, "manifest trailer does not match"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::IoError(e) => Some(e),
+            ManifestError::InvalidFingerprint(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ManifestError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            ManifestError::Truncated
+        } else {
+            ManifestError::IoError(error)
+        }
+    }
+}
+
+impl From<FingerprintError> for ManifestError {
+    fn from(error: FingerprintError) -> Self {
+        ManifestError::InvalidFingerprint(error)
+    }
+}
+
+///
+/// Describes one chunk recorded in a manifest.
+///
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    /// The hash value as of the end of the chunk.
+    pub hash: u64,
+    /// Starting byte position within the source.
+    pub offset: u64,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+    /// Content digest of the chunk, empty if the manifest has no digests.
+    #[cfg_attr(feature = "serde", serde(with = "crate::bytes"))]
+    pub digest: Vec<u8>,
+}
+
+impl ManifestEntry {
+    ///
+    /// Create an entry without a digest.
+    ///
+    pub fn new(hash: u64, offset: u64, length: usize) -> Self {
+        Self {
+            hash,
+            offset,
+            length,
+            digest: Vec::new(),
+        }
+    }
+
+    ///
+    /// Create an entry with the given digest.
+    ///
+    pub fn with_digest(hash: u64, offset: u64, length: usize, digest: &[u8]) -> Self {
+        Self {
+            hash,
+            offset,
+            length,
+            digest: digest.to_vec(),
+        }
+    }
+}
+
+impl From<ChunkInfo> for ManifestEntry {
+    fn from(info: ChunkInfo) -> Self {
+        ManifestEntry::new(info.hash, info.offset, info.length)
+    }
+}
+
+///
+/// Writes a manifest to the given [`Write`], one entry at a time.
+///
+/// The entries must be written in order, starting at offset zero, each chunk
+/// starting where the previous one ended. The manifest is complete once
+/// [`finish()`](ManifestWriter::finish) has written the trailer.
+///
+/// The writer issues several small writes per entry, hence a buffered writer,
+/// such as [`std::io::BufWriter`], is recommended when writing to a file.
+///
+pub struct ManifestWriter<W: Write> {
+    inner: W,
+    digest_len: usize,
+    count: u64,
+    total: u64,
+}
+
+impl<W: Write> ManifestWriter<W> {
+    ///
+    /// Write the header of a manifest for chunks produced by the chunker with
+    /// the given fingerprint, each having a digest of `digest_len` bytes.
+    ///
+    pub fn new(
+        mut inner: W,
+        fingerprint: &Fingerprint,
+        digest_len: usize,
+    ) -> Result<Self, ManifestError> {
+        if digest_len > MAX_DIGEST_LEN {
+            return Err(ManifestError::InvalidDigestLength(digest_len));
+        }
+        let mut header = [0u8; HEADER_LEN];
+        header[0..4].copy_from_slice(&MAGIC);
+        header[4] = FORMAT_VERSION;
+        header[5] = digest_len as u8;
+        header[8..].copy_from_slice(&fingerprint.to_bytes());
+        inner.write_all(&header)?;
+        Ok(Self {
+            inner,
+            digest_len,
+            count: 0,
+            total: 0,
+        })
+    }
+
+    ///
+    /// Write the next entry of the manifest.
+    ///
+    pub fn write_entry(&mut self, entry: &ManifestEntry) -> Result<(), ManifestError> {
+        if entry.offset != self.total {
+            return Err(ManifestError::InvalidOffset(entry.offset));
+        }
+        if entry.length == 0 || entry.length > u32::MAX as usize {
+            return Err(ManifestError::InvalidLength(entry.length));
+        }
+        if entry.digest.len() != self.digest_len {
+            return Err(ManifestError::InvalidDigestLength(entry.digest.len()));
+        }
+        let mut fixed = [0u8; ENTRY_LEN];
+        fixed[0..4].copy_from_slice(&(entry.length as u32).to_le_bytes());
+        fixed[4..12].copy_from_slice(&entry.hash.to_le_bytes());
+        self.inner.write_all(&fixed)?;
+        self.inner.write_all(&entry.digest)?;
+        self.count += 1;
+        self.total += entry.length as u64;
+        Ok(())
+    }
+
+    ///
+    /// Write the trailer, completing the manifest, and return the underlying
+    /// writer, after flushing it.
+    ///
+    pub fn finish(mut self) -> Result<W, ManifestError> {
+        let mut trailer = [0u8; 20];
+        trailer[4..12].copy_from_slice(&self.count.to_le_bytes());
+        trailer[12..20].copy_from_slice(&self.total.to_le_bytes());
+        self.inner.write_all(&trailer)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+///
+/// Reads a manifest from the given [`Read`], one entry at a time.
+///
+/// The header is read by [`new()`](ManifestReader::new), after which the
+/// entries can be read with [`read_entry()`](ManifestReader::read_entry) or
+/// via the [`Iterator`] trait. Reaching the end of the input before the
+/// trailer results in [`ManifestError::Truncated`].
+///
+/// The reader issues several small reads per entry, hence a buffered reader,
+/// such as [`std::io::BufReader`], is recommended when reading from a file.
+///
+pub struct ManifestReader<R: Read> {
+    inner: R,
+    fingerprint: Fingerprint,
+    digest_len: usize,
+    count: u64,
+    total: u64,
+    done: bool,
+}
+
+impl<R: Read> ManifestReader<R> {
+    ///
+    /// Read the header of the manifest from the given source.
+    ///
+    pub fn new(mut inner: R) -> Result<Self, ManifestError> {
+        let mut header = [0u8; HEADER_LEN];
+        inner.read_exact(&mut header)?;
+        if header[0..4] != MAGIC {
+            return Err(ManifestError::InvalidMagic);
+        }
+        if header[4] == 0 || header[4] > FORMAT_VERSION {
+            return Err(ManifestError::UnsupportedVersion(header[4]));
+        }
+        let fingerprint = Fingerprint::from_bytes(&header[8..])?;
+        Ok(Self {
+            inner,
+            fingerprint,
+            digest_len: header[5] as usize,
+            count: 0,
+            total: 0,
+            done: false,
+        })
+    }
+
+    /// The fingerprint of the chunker that produced the chunks.
+    pub fn fingerprint(&self) -> &Fingerprint {
+        &self.fingerprint
+    }
+
+    /// The length in bytes of the digest of each chunk.
+    pub fn digest_len(&self) -> usize {
+        self.digest_len
+    }
+
+    ///
+    /// Read the next entry, returning `None` once the trailer has been read.
+    ///
+    pub fn read_entry(&mut self) -> Result<Option<ManifestEntry>, ManifestError> {
+        if self.done {
+            return Ok(None);
+        }
+        let mut fixed = [0u8; ENTRY_LEN];
+        self.inner.read_exact(&mut fixed)?;
+        let length = u32::from_le_bytes(fixed[0..4].try_into().unwrap()) as usize;
+        if length == 0 {
+            // the trailer starts with a zero length, which no chunk can have
+            let count = u64::from_le_bytes(fixed[4..12].try_into().unwrap());
+            let mut total = [0u8; 8];
+            self.inner.read_exact(&mut total)?;
+            self.done = true;
+            if count != self.count || u64::from_le_bytes(total) != self.total {
+                return Err(ManifestError::InvalidTrailer);
+            }
+            return Ok(None);
+        }
+        let mut digest = vec![0u8; self.digest_len];
+        self.inner.read_exact(&mut digest)?;
+        let entry = ManifestEntry {
+            hash: u64::from_le_bytes(fixed[4..12].try_into().unwrap()),
+            offset: self.total,
+            length,
+            digest,
+        };
+        self.count += 1;
+        self.total += length as u64;
+        Ok(Some(entry))
+    }
+}
+
+impl<R: Read> Iterator for ManifestReader<R> {
+    type Item = Result<ManifestEntry, ManifestError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.read_entry();
+        if result.is_err() {
+            // do not continue reading from the middle of an entry
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::Chunker;
+    use crate::config::{Algorithm, ChunkerConfig, Normalization};
+    use crate::v2020::FastCDC;
+    use std::fs;
+
+    fn fingerprint() -> Fingerprint {
+        ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .seed(666)
+            .try_build()
+            .unwrap()
+            .fingerprint(Algorithm::V2020)
+            .unwrap()
+    }
+
+    fn sekien_entries() -> Vec<ManifestEntry> {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = fingerprint().config().unwrap();
+        FastCDC::with_config(&contents, &config)
+            .unwrap()
+            .map(|chunk| {
+                let data = &contents[chunk.offset..chunk.offset + chunk.length];
+                let digest = blake3::hash(data);
+                ManifestEntry::with_digest(
+                    chunk.hash,
+                    chunk.offset as u64,
+                    chunk.length,
+                    digest.as_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    fn write_manifest(entries: &[ManifestEntry], digest_len: usize) -> Vec<u8> {
+        let mut writer = ManifestWriter::new(Vec::new(), &fingerprint(), digest_len).unwrap();
+        for entry in entries {
+            writer.write_entry(entry).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_manifest_fixture() {
+        // the fixture pins the format, any change must bump the version
+        let expected = fs::read("test/fixtures/SekienAkashita.manifest").unwrap();
+        let entries = sekien_entries();
+        let bytes = write_manifest(&entries, 32);
+        assert_eq!(bytes, expected);
+        let reader = ManifestReader::new(expected.as_slice()).unwrap();
+        assert_eq!(reader.fingerprint(), &fingerprint());
+        assert_eq!(reader.digest_len(), 32);
+        let actual: Vec<ManifestEntry> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(actual, entries);
+        assert_eq!(actual.len(), 6);
+        assert_eq!(actual.last().unwrap().offset, 95165);
+    }
+
+    #[test]
+    fn test_manifest_without_digests() {
+        let entries: Vec<ManifestEntry> = sekien_entries()
+            .iter()
+            .map(|e| ManifestEntry::new(e.hash, e.offset, e.length))
+            .collect();
+        let bytes = write_manifest(&entries, 0);
+        assert_eq!(bytes.len(), HEADER_LEN + entries.len() * ENTRY_LEN + 20);
+        let mut reader = ManifestReader::new(bytes.as_slice()).unwrap();
+        for entry in &entries {
+            assert_eq!(reader.read_entry().unwrap().as_ref(), Some(entry));
+        }
+        assert!(reader.read_entry().unwrap().is_none());
+        assert!(reader.read_entry().unwrap().is_none());
+        // an empty manifest
+        let bytes = write_manifest(&[], 0);
+        let mut reader = ManifestReader::new(bytes.as_slice()).unwrap();
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_manifest_chunk_info() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let mut entries = Vec::new();
+        while let Some(Ok((info, _))) = chunker.next_entry() {
+            entries.push(ManifestEntry::from(info));
+        }
+        let bytes = write_manifest(&entries, 0);
+        let reader = ManifestReader::new(bytes.as_slice()).unwrap();
+        let read: Vec<ManifestEntry> = reader.map(|e| e.unwrap()).collect();
+        assert_eq!(read, entries);
+        let expected: Vec<(u64, u64, usize)> = FastCDC::new(&contents, 4096, 16384, 65535)
+            .map(|c| (c.hash, c.offset as u64, c.length))
+            .collect();
+        let spans: Vec<(u64, u64, usize)> =
+            read.iter().map(|e| (e.hash, e.offset, e.length)).collect();
+        assert_eq!(spans, expected);
+    }
+
+    #[test]
+    fn test_manifest_write_errors() {
+        let print = fingerprint();
+        assert!(matches!(
+            ManifestWriter::new(Vec::new(), &print, 256),
+            Err(ManifestError::InvalidDigestLength(256))
+        ));
+        let mut writer = ManifestWriter::new(Vec::new(), &print, 4).unwrap();
+        let entry = ManifestEntry::with_digest(1, 0, 100, &[1, 2, 3, 4]);
+        writer.write_entry(&entry).unwrap();
+        assert!(matches!(
+            writer.write_entry(&ManifestEntry::with_digest(1, 50, 100, &[0; 4])),
+            Err(ManifestError::InvalidOffset(50))
+        ));
+        assert!(matches!(
+            writer.write_entry(&ManifestEntry::with_digest(1, 100, 0, &[0; 4])),
+            Err(ManifestError::InvalidLength(0))
+        ));
+        assert!(matches!(
+            writer.write_entry(&ManifestEntry::new(1, 100, 100)),
+            Err(ManifestError::InvalidDigestLength(0))
+        ));
+    }
+
+    #[test]
+    fn test_manifest_read_errors() {
+        let entries = sekien_entries();
+        let bytes = write_manifest(&entries, 32);
+        assert!(matches!(
+            ManifestReader::new(&bytes[..10]),
+            Err(ManifestError::Truncated)
+        ));
+        let mut other = bytes.clone();
+        other[0] = b'X';
+        assert!(matches!(
+            ManifestReader::new(other.as_slice()),
+            Err(ManifestError::InvalidMagic)
+        ));
+        let mut other = bytes.clone();
+        other[4] = FORMAT_VERSION + 1;
+        assert!(matches!(
+            ManifestReader::new(other.as_slice()),
+            Err(ManifestError::UnsupportedVersion(_))
+        ));
+        let mut other = bytes.clone();
+        other[8] = b'X';
+        assert!(matches!(
+            ManifestReader::new(other.as_slice()),
+            Err(ManifestError::InvalidFingerprint(
+                FingerprintError::InvalidMagic
+            ))
+        ));
+        // missing the trailer, or part of it
+        for cut in [bytes.len() - 20, bytes.len() - 1, HEADER_LEN + 5] {
+            let reader = ManifestReader::new(&bytes[..cut]).unwrap();
+            let results: Vec<_> = reader.collect();
+            assert!(matches!(
+                results.last(),
+                Some(Err(ManifestError::Truncated))
+            ));
+        }
+        // a trailer that does not match the entries
+        let mut other = bytes.clone();
+        let count = other.len() - 16;
+        other[count] += 1;
+        let reader = ManifestReader::new(other.as_slice()).unwrap();
+        let results: Vec<_> = reader.collect();
+        assert_eq!(results.len(), entries.len() + 1);
+        assert!(matches!(
+            results.last(),
+            Some(Err(ManifestError::InvalidTrailer))
+        ));
+    }
+}
@@ -0,0 +1,377 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`MerkleTree`], a hash tree built over the digests of
+//! the chunks of a file, in chunk order, whose root identifies the whole file,
+//! and [`MerkleProof`], which proves that a chunk is part of the file with a
+//! given root without the other chunks, such as to validate a partial
+//! download.
+//!
+//! The tree has the shape of the trees described in RFC 6962: for more than
+//! one chunk, the left subtree holds the largest power of two of the chunks
+//! that is less than their number, and the right subtree holds the rest. The
+//! nodes are hashed with the same [`ChunkHasher`] as the chunks, and the input
+//! to the hash of each node starts with a tag and the format version:
+//!
+//! * leaf: `0x00`, [`FORMAT_VERSION`], digest of the chunk
+//! * interior node: `0x01`, [`FORMAT_VERSION`], left child, right child
+//! * root of a file without chunks: `0x02`, [`FORMAT_VERSION`]
+//!
+//! The tags ensure that a leaf cannot be mistaken for an interior node, while
+//! the version ensures that a root computed with a different encoding never
+//! matches.
+//!
+//! ```
+//! # #[cfg(feature = "blake3")] {
+//! use fastcdc::merkle::MerkleTree;
+//! use fastcdc::v2020::FastCDC;
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+//! let (chunks, digests): (Vec<_>, Vec<_>) =
+//!     chunker.with_hasher(blake3::Hasher::new()).unzip();
+//! let mut hasher = blake3::Hasher::new();
+//! let tree = MerkleTree::new(&mut hasher, digests);
+//! let root = *tree.root();
+//!
+//! // ...later, having downloaded only the third chunk and its proof, and
+//! // knowing the root and the number of chunks from a trusted source...
+//! let count = tree.len();
+//! let proof = tree.proof(2).unwrap();
+//! let chunk = &chunks[2];
+//! let data = &contents[chunk.offset..chunk.offset + chunk.length];
+//! assert!(proof.verify_chunk(&mut hasher, 2, count, data, &root));
+//! # }
+//! ```
+use crate::digest::ChunkHasher;
+use alloc::vec::Vec;
+
+/// Version of the node encoding used by this release of the crate.
+pub const FORMAT_VERSION: u8 = 1;
+
+// Leading byte of the input to the hash of each kind of node.
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+const EMPTY_TAG: u8 = 0x02;
+
+///
+/// A Merkle tree over the digests of the chunks of a file.
+///
+/// The tree holds the hash of every node, hence producing a proof for any of
+/// the chunks does not require hashing.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MerkleTree<D> {
+    // number of chunks
+    count: usize,
+    // hashes of the nodes of each level, from the leaves up to the root, where
+    // the last node of a level with an odd number of nodes is moved up as-is
+    levels: Vec<Vec<D>>,
+}
+
+impl<D: AsRef<[u8]> + Clone + Eq> MerkleTree<D> {
+    ///
+    /// Build the tree over the given digests of the chunks, in chunk order,
+    /// using the hasher that produced them to hash the nodes.
+    ///
+    pub fn new<H, I>(hasher: &mut H, digests: I) -> Self
+    where
+        H: ChunkHasher<Digest = D>,
+        I: IntoIterator<Item = D>,
+    {
+        let leaves: Vec<D> = digests
+            .into_iter()
+            .map(|digest| leaf_hash(hasher, &digest))
+            .collect();
+        let count = leaves.len();
+        if count == 0 {
+            hasher.update(&[EMPTY_TAG, FORMAT_VERSION]);
+            let root = hasher.finalize_reset();
+            return Self {
+                count,
+                levels: alloc::vec![alloc::vec![root]],
+            };
+        }
+        let mut levels = alloc::vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let level = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(hasher, left, right),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+        }
+        Self { count, levels }
+    }
+
+    /// The root hash, which identifies the file.
+    pub fn root(&self) -> &D {
+        &self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The number of chunks in the tree.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the tree has no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    ///
+    /// Produce the proof that the chunk at the given index is part of the
+    /// tree, or `None` if the index is out of range.
+    ///
+    pub fn proof(&self, index: usize) -> Option<MerkleProof<D>> {
+        if index >= self.count {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                path.push(level[sibling].clone());
+            }
+            position /= 2;
+        }
+        Some(MerkleProof { path })
+    }
+}
+
+///
+/// Proves that a chunk is part of the tree with a particular root, produced by
+/// [`MerkleTree::proof()`].
+///
+/// The root does not commit to the number of chunks, hence the position of
+/// the chunk and the number of chunks in the file must come from the caller,
+/// from the same trusted source as the root, as with the tree size in RFC
+/// 9162. Otherwise a proof for a smaller tree could place a chunk at the wrong
+/// position.
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MerkleProof<D> {
+    /// Hashes of the siblings of the nodes from the leaf up to the root.
+    pub path: Vec<D>,
+}
+
+impl<D: AsRef<[u8]> + Clone + Eq> MerkleProof<D> {
+    ///
+    /// Verify that the chunk with the given digest is at the given index of
+    /// the tree of `count` chunks with the given root.
+    ///
+    /// This follows the verification algorithm of RFC 9162, section 2.1.3.2.
+    ///
+    pub fn verify<H: ChunkHasher<Digest = D>>(
+        &self,
+        hasher: &mut H,
+        index: usize,
+        count: usize,
+        digest: &D,
+        root: &D,
+    ) -> bool {
+        if index >= count {
+            return false;
+        }
+        let mut position = index;
+        let mut last = count - 1;
+        let mut hash = leaf_hash(hasher, digest);
+        for sibling in &self.path {
+            if last == 0 {
+                return false;
+            }
+            if position & 1 == 1 || position == last {
+                hash = node_hash(hasher, sibling, &hash);
+                // skip the levels at which this node was moved up as-is
+                while position & 1 == 0 && position != 0 {
+                    position /= 2;
+                    last /= 2;
+                }
+            } else {
+                hash = node_hash(hasher, &hash, sibling);
+            }
+            position /= 2;
+            last /= 2;
+        }
+        last == 0 && hash == *root
+    }
+
+    ///
+    /// Verify that the given chunk is at the given index of the tree of
+    /// `count` chunks with the given root, computing the digest of the chunk
+    /// with the given hasher.
+    ///
+    pub fn verify_chunk<H: ChunkHasher<Digest = D>>(
+        &self,
+        hasher: &mut H,
+        index: usize,
+        count: usize,
+        data: &[u8],
+        root: &D,
+    ) -> bool {
+        let digest = hasher.digest(data);
+        self.verify(hasher, index, count, &digest, root)
+    }
+}
+
+fn leaf_hash<H: ChunkHasher>(hasher: &mut H, digest: &H::Digest) -> H::Digest
+where
+    H::Digest: AsRef<[u8]>,
+{
+    hasher.update(&[LEAF_TAG, FORMAT_VERSION]);
+    hasher.update(digest.as_ref());
+    hasher.finalize_reset()
+}
+
+fn node_hash<H: ChunkHasher>(hasher: &mut H, left: &H::Digest, right: &H::Digest) -> H::Digest
+where
+    H::Digest: AsRef<[u8]>,
+{
+    hasher.update(&[NODE_TAG, FORMAT_VERSION]);
+    hasher.update(left.as_ref());
+    hasher.update(right.as_ref());
+    hasher.finalize_reset()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::test_util::Sip;
+    use crate::v2020::FastCDC;
+    use std::fs;
+
+    // Collects the input, such that the digest shows how it was computed.
+    struct Recorder(Vec<u8>);
+
+    impl ChunkHasher for Recorder {
+        type Digest = Vec<u8>;
+
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data);
+        }
+
+        fn finalize_reset(&mut self) -> Vec<u8> {
+            core::mem::take(&mut self.0)
+        }
+    }
+
+    fn digests(count: u8) -> Vec<[u8; 16]> {
+        (0..count).map(|i| Sip::default().digest(&[i])).collect()
+    }
+
+    #[test]
+    fn test_merkle_encoding() {
+        let mut hasher = Recorder(Vec::new());
+        let tree = MerkleTree::new(&mut hasher, Vec::<Vec<u8>>::new());
+        assert!(tree.is_empty());
+        assert_eq!(tree.root(), &[2, 1]);
+        assert!(tree.proof(0).is_none());
+        let tree = MerkleTree::new(&mut hasher, [vec![7]]);
+        assert_eq!(tree.root(), &[0, 1, 7]);
+        // the left subtree has the largest power of two less than the count
+        let tree = MerkleTree::new(&mut hasher, [vec![7], vec![8], vec![9]]);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.root(), &[1, 1, 1, 1, 0, 1, 7, 0, 1, 8, 0, 1, 9]);
+        let tree = MerkleTree::new(&mut hasher, (1..=5).map(|i| vec![i]));
+        let left = [1, 1, 1, 1, 0, 1, 1, 0, 1, 2, 1, 1, 0, 1, 3, 0, 1, 4];
+        let expected: Vec<u8> = [1, 1]
+            .iter()
+            .chain(&left)
+            .chain(&[0, 1, 5])
+            .copied()
+            .collect();
+        assert_eq!(tree.root(), &expected);
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        let mut hasher = Sip::default();
+        for count in 1..=33 {
+            let digests = digests(count);
+            let tree = MerkleTree::new(&mut hasher, digests.clone());
+            let root = *tree.root();
+            for (index, digest) in digests.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                let count = count as usize;
+                assert!(proof.path.len() <= 6);
+                assert!(proof.verify(&mut hasher, index, count, digest, &root));
+                // another chunk, or another root, does not verify
+                let other = digests[(index + 1) % count];
+                assert_eq!(
+                    proof.verify(&mut hasher, index, count, &other, &root),
+                    count == 1
+                );
+                assert!(!proof.verify(&mut hasher, index, count, digest, &other));
+                // nor does another position
+                let moved = (index + 1) % count;
+                assert_eq!(
+                    proof.verify(&mut hasher, moved, count, digest, &root),
+                    count == 1
+                );
+                assert!(!proof.verify(&mut hasher, count, count, digest, &root));
+                if let Some(first) = proof.path.first() {
+                    let mut altered = proof.clone();
+                    altered.path[0][0] ^= 1;
+                    assert!(!altered.verify(&mut hasher, index, count, digest, &root));
+                    let mut longer = proof.clone();
+                    longer.path.push(*first);
+                    assert!(!longer.verify(&mut hasher, index, count, digest, &root));
+                    let mut shorter = proof.clone();
+                    shorter.path.pop();
+                    assert!(!shorter.verify(&mut hasher, index, count, digest, &root));
+                }
+            }
+            assert!(tree.proof(count as usize).is_none());
+        }
+    }
+
+    #[test]
+    fn test_merkle_forged_position() {
+        let mut hasher = Sip::default();
+        let digests = digests(3);
+        let tree = MerkleTree::new(&mut hasher, digests.clone());
+        let root = *tree.root();
+        // the root of 3 chunks is also the root of 2 chunks, the first being
+        // the node over chunks 0 and 1, hence the path of chunk 2 places it at
+        // index 1 of a tree of 2 chunks
+        let forged = MerkleProof {
+            path: vec![tree.levels[1][0]],
+        };
+        assert_eq!(forged, tree.proof(2).unwrap());
+        assert!(forged.verify(&mut hasher, 2, 3, &digests[2], &root));
+        assert!(!forged.verify(&mut hasher, 1, 3, &digests[2], &root));
+        // which is why the number of chunks must come from a trusted source
+        let node = tree.levels[1][0];
+        let leaf = leaf_hash(&mut hasher, &digests[2]);
+        assert_eq!(node_hash(&mut hasher, &node, &leaf), root);
+    }
+
+    #[test]
+    fn test_merkle_chunks() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let (chunks, digests): (Vec<_>, Vec<_>) = chunker.with_hasher(Sip::default()).unzip();
+        let mut hasher = Sip::default();
+        let tree = MerkleTree::new(&mut hasher, digests);
+        assert_eq!(tree.len(), 5);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            let data = &contents[chunk.offset..chunk.offset + chunk.length];
+            let count = tree.len();
+            assert!(proof.verify_chunk(&mut hasher, index, count, data, tree.root()));
+            assert!(!proof.verify_chunk(&mut hasher, index, count, &data[1..], tree.root()));
+        }
+        // a change to any chunk changes the root
+        let mut altered = contents.clone();
+        altered[50_000] ^= 1;
+        let chunker = FastCDC::new(&altered, 4096, 16384, 65535);
+        let digests = chunker.with_hasher(Sip::default()).map(|(_, d)| d);
+        assert_ne!(MerkleTree::new(&mut hasher, digests).root(), tree.root());
+    }
+}
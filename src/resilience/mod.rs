@@ -0,0 +1,529 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`Analyzer`], which measures how well the chunk
+//! boundaries of a chunking configuration withstand edits to the data, by
+//! applying synthetic edits at random offsets, chunking the result, and
+//! counting the chunks that did not exist before the edit, which are the
+//! chunks that a repository would have to store anew.
+//!
+//! An ideal content-defined chunker changes only the chunks that overlap the
+//! edit, plus at most one after it, until the boundaries line up again. The
+//! same analysis with different algorithms or seeds compares them
+//! quantitatively.
+//!
+//! ```
+//! use fastcdc::config::{Algorithm, ChunkerConfig};
+//! use fastcdc::resilience::{Analyzer, EditKind};
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let config = ChunkerConfig::builder(2048, 8192, 32768).try_build().unwrap();
+//! let analyzer = Analyzer::new(Algorithm::V2020, &config).unwrap().edits_per_kind(10);
+//! let report = analyzer.run(&contents);
+//! let inserts = report.summary(EditKind::Insert).unwrap();
+//! assert_eq!(inserts.edits, 10);
+//! println!("{report}");
+//! ```
+use crate::chunker::{ChunkInfo, Chunker};
+use crate::config::{Algorithm, ChunkerConfig, ConfigError};
+use crate::{ronomon, v2016, v2020};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+
+///
+/// The kinds of synthetic edits applied by the [`Analyzer`].
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum EditKind {
+    /// Insert new bytes.
+    Insert,
+    /// Delete a range of bytes.
+    Delete,
+    /// Overwrite a range of bytes with new bytes.
+    Replace,
+    /// Move a block of bytes to another offset.
+    Move,
+}
+
+impl fmt::Display for EditKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EditKind::Insert => "insert",
+            EditKind::Delete => "delete",
+            EditKind::Replace => "replace",
+            EditKind::Move => "move",
+        })
+    }
+}
+
+///
+/// An edit of a sequence of bytes.
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Edit {
+    /// Insert the bytes at the offset.
+    Insert { offset: usize, data: Vec<u8> },
+    /// Delete the given number of bytes at the offset.
+    Delete { offset: usize, length: usize },
+    /// Overwrite the bytes at the offset, without going past the end.
+    Replace { offset: usize, data: Vec<u8> },
+    /// Remove the block of the given length at `from`, then insert it at `to`
+    /// within the remaining bytes.
+    Move {
+        from: usize,
+        length: usize,
+        to: usize,
+    },
+}
+
+impl Edit {
+    /// The kind of this edit.
+    pub fn kind(&self) -> EditKind {
+        match self {
+            Edit::Insert { .. } => EditKind::Insert,
+            Edit::Delete { .. } => EditKind::Delete,
+            Edit::Replace { .. } => EditKind::Replace,
+            Edit::Move { .. } => EditKind::Move,
+        }
+    }
+
+    ///
+    /// Return a copy of the given bytes with this edit applied.
+    ///
+    /// Offsets and lengths beyond the end of the bytes are clamped.
+    ///
+    pub fn apply(&self, source: &[u8]) -> Vec<u8> {
+        let mut result = source.to_vec();
+        match self {
+            Edit::Insert { offset, data } => {
+                let offset = (*offset).min(result.len());
+                result.splice(offset..offset, data.iter().copied());
+            }
+            Edit::Delete { offset, length } => {
+                let start = (*offset).min(result.len());
+                let end = start.saturating_add(*length).min(result.len());
+                result.drain(start..end);
+            }
+            Edit::Replace { offset, data } => {
+                let start = (*offset).min(result.len());
+                let end = start.saturating_add(data.len()).min(result.len());
+                result[start..end].copy_from_slice(&data[..end - start]);
+            }
+            Edit::Move { from, length, to } => {
+                let start = (*from).min(result.len());
+                let end = start.saturating_add(*length).min(result.len());
+                let block: Vec<u8> = result.drain(start..end).collect();
+                let to = (*to).min(result.len());
+                result.splice(to..to, block);
+            }
+        }
+        result
+    }
+}
+
+///
+/// The effect of a single edit on the chunks of the data.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EditOutcome {
+    /// The edit that was applied.
+    pub edit: Edit,
+    /// Number of chunks of the edited data.
+    pub chunk_count: usize,
+    /// Number of chunks of the edited data that are not chunks of the original.
+    pub new_chunks: usize,
+    /// Sum of the lengths of the new chunks, which must be stored anew.
+    pub new_bytes: u64,
+}
+
+///
+/// The outcomes of all of the edits of one kind.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct KindSummary {
+    /// The kind of edit.
+    pub kind: EditKind,
+    /// Number of edits of this kind.
+    pub edits: usize,
+    /// Mean number of new chunks per edit.
+    pub mean_new_chunks: f64,
+    /// Mean number of bytes stored anew per edit.
+    pub mean_new_bytes: f64,
+    /// Largest number of bytes stored anew by any one edit.
+    pub max_new_bytes: u64,
+}
+
+///
+/// The results of [`Analyzer::run()`].
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResilienceReport {
+    /// The algorithm that was analyzed.
+    pub algorithm: Algorithm,
+    /// The configuration that was analyzed.
+    pub config: ChunkerConfig,
+    /// Number of chunks of the original data.
+    pub original_chunks: usize,
+    /// Outcome of each edit, in the order they were applied.
+    pub outcomes: Vec<EditOutcome>,
+}
+
+impl ResilienceReport {
+    /// Summarize the outcomes of the edits of the given kind, if any.
+    pub fn summary(&self, kind: EditKind) -> Option<KindSummary> {
+        let outcomes: Vec<&EditOutcome> = self
+            .outcomes
+            .iter()
+            .filter(|o| o.edit.kind() == kind)
+            .collect();
+        if outcomes.is_empty() {
+            return None;
+        }
+        let edits = outcomes.len();
+        let new_chunks: usize = outcomes.iter().map(|o| o.new_chunks).sum();
+        let new_bytes: u64 = outcomes.iter().map(|o| o.new_bytes).sum();
+        Some(KindSummary {
+            kind,
+            edits,
+            mean_new_chunks: new_chunks as f64 / edits as f64,
+            mean_new_bytes: new_bytes as f64 / edits as f64,
+            max_new_bytes: outcomes.iter().map(|o| o.new_bytes).max().unwrap_or(0),
+        })
+    }
+}
+
+impl fmt::Display for ResilienceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} min={} avg={} max={} nc={} seed={}: {} chunks",
+            self.algorithm,
+            self.config.min_size(),
+            self.config.avg_size(),
+            self.config.max_size(),
+            self.config.normalization(),
+            self.config.seed(),
+            self.original_chunks
+        )?;
+        for kind in ALL_KINDS {
+            if let Some(summary) = self.summary(kind) {
+                write!(
+                    f,
+                    "\n  {:<8} edits={} new chunks={:.2} new bytes={:.0} (max {})",
+                    kind,
+                    summary.edits,
+                    summary.mean_new_chunks,
+                    summary.mean_new_bytes,
+                    summary.max_new_bytes
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const ALL_KINDS: [EditKind; 4] = [
+    EditKind::Insert,
+    EditKind::Delete,
+    EditKind::Replace,
+    EditKind::Move,
+];
+
+///
+/// Applies synthetic edits to data and measures how many chunks change.
+///
+/// By default, 100 edits of each kind are applied, each of 16 bytes, or for a
+/// move, of a block the size of the average chunk, at offsets chosen by a
+/// pseudorandom generator with a fixed seed, such that the same analysis of
+/// the same data always applies the same edits.
+///
+#[derive(Debug, Clone)]
+pub struct Analyzer {
+    algorithm: Algorithm,
+    config: ChunkerConfig,
+    kinds: Vec<EditKind>,
+    edits_per_kind: usize,
+    edit_length: usize,
+    random_seed: u64,
+}
+
+impl Analyzer {
+    ///
+    /// Construct an [`Analyzer`] of the given algorithm and configuration.
+    ///
+    /// Returns `ConfigError::Unsupported` if the algorithm cannot honor the
+    /// configuration.
+    ///
+    pub fn new(algorithm: Algorithm, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        algorithm.check(config)?;
+        Ok(Self {
+            algorithm,
+            config: *config,
+            kinds: ALL_KINDS.to_vec(),
+            edits_per_kind: 100,
+            edit_length: 16,
+            random_seed: 1,
+        })
+    }
+
+    /// Set the kinds of edits to apply.
+    pub fn kinds(mut self, kinds: Vec<EditKind>) -> Self {
+        self.kinds = kinds;
+        self
+    }
+
+    /// Set the number of edits of each kind.
+    pub fn edits_per_kind(mut self, count: usize) -> Self {
+        self.edits_per_kind = count;
+        self
+    }
+
+    /// Set the number of bytes inserted, deleted, or replaced by each edit.
+    pub fn edit_length(mut self, length: usize) -> Self {
+        self.edit_length = length;
+        self
+    }
+
+    /// Set the seed of the generator of the edits.
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = seed;
+        self
+    }
+
+    /// Find the chunks of the given data.
+    pub fn chunk(&self, data: &[u8]) -> Vec<ChunkInfo> {
+        // the configuration was checked when constructing the analyzer
+        let unsupported = "configuration checked by Analyzer::new()";
+        let mut chunker: Box<dyn Chunker<Error = Infallible>> = match self.algorithm {
+            Algorithm::Ronomon => {
+                Box::new(ronomon::FastCDC::with_config(data, &self.config).expect(unsupported))
+            }
+            Algorithm::V2016 => {
+                Box::new(v2016::FastCDC::with_config(data, &self.config).expect(unsupported))
+            }
+            Algorithm::V2020 => {
+                Box::new(v2020::FastCDC::with_config(data, &self.config).expect(unsupported))
+            }
+        };
+        let mut chunks = Vec::new();
+        while let Some(Ok((info, _))) = chunker.next_entry() {
+            chunks.push(info);
+        }
+        chunks
+    }
+
+    /// Measure the effect of the given edit of the given data.
+    pub fn measure(&self, data: &[u8], edit: &Edit) -> EditOutcome {
+        let original = self.chunk(data);
+        self.measure_with(data, &chunk_set(data, &original), edit)
+    }
+
+    ///
+    /// Apply the configured number of edits of each kind to the given data, one
+    /// at a time, measuring the effect of each.
+    ///
+    pub fn run(&self, data: &[u8]) -> ResilienceReport {
+        let original = self.chunk(data);
+        let known = chunk_set(data, &original);
+        let mut random = XorShift::new(self.random_seed);
+        let mut outcomes = Vec::new();
+        for kind in &self.kinds {
+            for _ in 0..self.edits_per_kind {
+                let edit = self.random_edit(*kind, data.len(), &mut random);
+                outcomes.push(self.measure_with(data, &known, &edit));
+            }
+        }
+        ResilienceReport {
+            algorithm: self.algorithm,
+            config: self.config,
+            original_chunks: original.len(),
+            outcomes,
+        }
+    }
+
+    fn measure_with(&self, data: &[u8], known: &HashSet<&[u8]>, edit: &Edit) -> EditOutcome {
+        let edited = edit.apply(data);
+        let chunks = self.chunk(&edited);
+        let mut new_chunks = 0;
+        let mut new_bytes = 0;
+        for chunk in &chunks {
+            let start = chunk.offset as usize;
+            if !known.contains(&edited[start..start + chunk.length]) {
+                new_chunks += 1;
+                new_bytes += chunk.length as u64;
+            }
+        }
+        EditOutcome {
+            edit: edit.clone(),
+            chunk_count: chunks.len(),
+            new_chunks,
+            new_bytes,
+        }
+    }
+
+    fn random_edit(&self, kind: EditKind, len: usize, random: &mut XorShift) -> Edit {
+        let length = self.edit_length.min(len);
+        match kind {
+            EditKind::Insert => Edit::Insert {
+                offset: random.below(len + 1),
+                data: random.bytes(self.edit_length),
+            },
+            EditKind::Delete => Edit::Delete {
+                offset: random.below(len - length + 1),
+                length,
+            },
+            EditKind::Replace => Edit::Replace {
+                offset: random.below(len - length + 1),
+                data: random.bytes(length),
+            },
+            EditKind::Move => {
+                let length = self.config.avg_size().min(len);
+                Edit::Move {
+                    from: random.below(len - length + 1),
+                    length,
+                    to: random.below(len - length + 1),
+                }
+            }
+        }
+    }
+}
+
+// The distinct chunks of the data, by their contents.
+fn chunk_set<'a>(data: &'a [u8], chunks: &[ChunkInfo]) -> HashSet<&'a [u8]> {
+    chunks
+        .iter()
+        .map(|c| &data[c.offset as usize..c.offset as usize + c.length])
+        .collect()
+}
+
+// Deterministic pseudorandom numbers (xorshift64*).
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // the state must not be zero
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // A number less than the bound, which must not be zero.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn bytes(&mut self, length: usize) -> Vec<u8> {
+        (0..length).map(|_| (self.next() >> 56) as u8).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Normalization;
+
+    #[test]
+    fn test_edit_apply() {
+        let source = b"0123456789";
+        let insert = Edit::Insert {
+            offset: 3,
+            data: b"ab".to_vec(),
+        };
+        assert_eq!(insert.apply(source), b"012ab3456789");
+        let delete = Edit::Delete {
+            offset: 8,
+            length: 5,
+        };
+        assert_eq!(delete.apply(source), b"01234567");
+        let replace = Edit::Replace {
+            offset: 8,
+            data: b"abc".to_vec(),
+        };
+        assert_eq!(replace.apply(source), b"01234567ab");
+        let moved = Edit::Move {
+            from: 1,
+            length: 3,
+            to: 4,
+        };
+        assert_eq!(moved.apply(source), b"0456123789");
+        assert_eq!(moved.kind(), EditKind::Move);
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut random = XorShift::new(42);
+        let data = random.bytes(262_144);
+        let config = ChunkerConfig::builder(1024, 4096, 16384)
+            .try_build()
+            .unwrap();
+        let analyzer = Analyzer::new(Algorithm::V2020, &config).unwrap();
+        let original = analyzer.chunk(&data);
+        // an edit that changes nothing
+        let same = Edit::Replace {
+            offset: 1000,
+            data: data[1000..1010].to_vec(),
+        };
+        let outcome = analyzer.measure(&data, &same);
+        assert_eq!(outcome.new_chunks, 0);
+        assert_eq!(outcome.chunk_count, original.len());
+        // a single byte in the middle of a chunk changes only that chunk
+        let chunk = original[10];
+        let offset = chunk.offset as usize + chunk.length / 2;
+        let flip = Edit::Replace {
+            offset,
+            data: vec![!data[offset]],
+        };
+        let outcome = analyzer.measure(&data, &flip);
+        assert!(outcome.new_chunks >= 1);
+        assert!(outcome.new_bytes >= chunk.length as u64);
+        assert!(outcome.new_bytes <= 3 * config.max_size() as u64);
+    }
+
+    #[test]
+    fn test_run() {
+        let mut random = XorShift::new(7);
+        let data = random.bytes(262_144);
+        let config = ChunkerConfig::builder(1024, 4096, 16384)
+            .try_build()
+            .unwrap();
+        for algorithm in [Algorithm::Ronomon, Algorithm::V2016, Algorithm::V2020] {
+            let analyzer = Analyzer::new(algorithm, &config)
+                .unwrap()
+                .edits_per_kind(20);
+            let report = analyzer.run(&data);
+            assert_eq!(report.outcomes.len(), 80);
+            assert_eq!(report, analyzer.run(&data));
+            for kind in ALL_KINDS {
+                let summary = report.summary(kind).unwrap();
+                assert_eq!(summary.edits, 20);
+                assert!(summary.mean_new_chunks >= 1.0, "{algorithm} {kind}");
+                // far fewer than all of the chunks
+                assert!(summary.mean_new_chunks < 5.0, "{algorithm} {kind}");
+            }
+            let text = report.to_string();
+            assert_eq!(text.lines().count(), 5);
+        }
+        // a different seed applies different edits
+        let analyzer = Analyzer::new(Algorithm::V2020, &config).unwrap();
+        let first = analyzer.clone().kinds(vec![EditKind::Insert]).run(&data);
+        let second = analyzer
+            .kinds(vec![EditKind::Insert])
+            .random_seed(2)
+            .run(&data);
+        assert!(second.summary(EditKind::Delete).is_none());
+        assert_ne!(first.outcomes, second.outcomes);
+        // ronomon supports only normalization level 1
+        let config = ChunkerConfig::builder(1024, 4096, 16384)
+            .normalization(Normalization::Level2)
+            .try_build()
+            .unwrap();
+        assert!(Analyzer::new(Algorithm::Ronomon, &config).is_err());
+    }
+}
@@ -24,6 +24,28 @@
 //! >   from the strict mask to the eager mask earlier.
 //! > * Masks use 1 bit of chunk size normalization instead of 2 bits of chunk
 //! >   size normalization.
+//!
+//! The `StreamCDC` implementation reads data from a `Read` into an internal
+//! buffer of `max_size` and produces `ChunkData` values from the `Iterator`,
+//! with the same cut points as `FastCDC`. With the `tokio` or `futures` feature
+//! enabled, the `AsyncStreamCDC` implementation does the same for an
+//! `AsyncRead` source, producing a `Stream` of `ChunkData` values.
+
+use crate::chunker::{ChunkInfo, ChunkResult, Chunker, Cut};
+use crate::config::{ChunkerConfig, ConfigError, Normalization, logarithm2};
+#[cfg(feature = "std")]
+use crate::digest::Digested;
+use crate::digest::{ChunkHasher, DigestedCDC};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::convert::Infallible;
+#[cfg(feature = "std")]
+use std::{fmt, io::Read};
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_stream_cdc;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use async_stream_cdc::*;
 
 /// Smallest acceptable value for the minimum chunk size.
 pub const MINIMUM_MIN: usize = 64;
@@ -40,6 +62,7 @@ pub const MAXIMUM_MAX: usize = 1_073_741_824;
 
 /// Represents a chunk, returned from the FastCDC iterator.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chunk {
     /// Hash found at this location.
     pub hash: u32,
@@ -128,80 +151,534 @@ impl<'a> FastCDC<'a> {
         }
     }
 
+    ///
+    /// Construct a new `FastCDC` using the validated parameters of the given
+    /// `ChunkerConfig`.
+    ///
+    /// This implementation always uses one bit of chunk size normalization and
+    /// a fixed gear table, so `ConfigError::Unsupported` is returned if the
+    /// configuration has a normalization level other than `Level1`, or a
+    /// non-zero seed.
+    ///
+    pub fn with_config(source: &'a [u8], config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        check_config(config)?;
+        Ok(FastCDC::new(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+        ))
+    }
+
+    ///
+    /// Find the next chunks, writing the hash and the end offset of each one
+    /// into `points` until either it is full or the source is exhausted, and
+    /// returning the number of cut points written, as with
+    /// [`v2020::FastCDC::cut_points_into()`]. If the chunker was created with
+    /// `eof` set to `false`, the remainder of the source that may yet be part
+    /// of a larger chunk is left unprocessed.
+    ///
+    /// [`v2020::FastCDC::cut_points_into()`]: crate::v2020::FastCDC::cut_points_into
+    ///
+    pub fn cut_points_into(&mut self, points: &mut [(u32, usize)]) -> usize {
+        let mut count = 0;
+        while count < points.len() {
+            let Some((hash, end, _)) = self.next_cut() else {
+                break;
+            };
+            points[count] = (hash, end);
+            count += 1;
+        }
+        count
+    }
+
+    ///
+    /// Append the hash and the end offset of each of the remaining chunks to
+    /// `points`, as with [`cut_points_into()`](FastCDC::cut_points_into).
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn collect_cut_points(&mut self, points: &mut Vec<(u32, usize)>) {
+        while let Some((hash, end, _)) = self.next_cut() {
+            points.push((hash, end));
+        }
+    }
+
+    // Find the next cut point, advancing past the chunk it ends.
+    fn next_cut(&mut self) -> Option<(u32, usize, Cut)> {
+        if self.bytes_remaining == 0 {
+            return None;
+        }
+        let (chunk_hash, chunk_size, cut) = self.cut(self.bytes_processed, self.bytes_remaining);
+        if chunk_size == 0 {
+            None
+        } else {
+            self.bytes_processed += chunk_size;
+            self.bytes_remaining -= chunk_size;
+            Some((chunk_hash, self.bytes_processed, cut))
+        }
+    }
+
     /// Returns the size of the next chunk.
-    fn cut(&self, mut source_offset: usize, mut source_size: usize) -> (u32, usize) {
-        if source_size <= self.min_size {
-            if !self.eof { (0, 0) } else { (0, source_size) }
+    fn cut(&self, source_offset: usize, source_size: usize) -> (u32, usize, Cut) {
+        cut(
+            &self.source[source_offset..source_offset + source_size],
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.mask_s,
+            self.mask_l,
+            self.eof,
+        )
+    }
+
+    ///
+    /// Produce the digest of each chunk along with the chunk, using the given
+    /// hasher, as with [`v2020::FastCDC::with_hasher()`].
+    ///
+    /// [`v2020::FastCDC::with_hasher()`]: crate::v2020::FastCDC::with_hasher
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> DigestedCDC<'a, Self, H> {
+        let source = self.source;
+        DigestedCDC::new(self, source, hasher, |chunk| {
+            chunk.offset..chunk.offset + chunk.length
+        })
+    }
+}
+
+///
+/// Find the next chunk cut point in the source, returning the hash, the size
+/// of the chunk, and the reason for the cut.
+///
+/// If `eof` is `false`, then the source is not the final block of data, and a
+/// size of zero is returned when a larger chunk may yet be found in subsequent
+/// data.
+///
+fn cut(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u32,
+    mask_l: u32,
+    eof: bool,
+) -> (u32, usize, Cut) {
+    let mut source_size = source.len();
+    if source_size <= min_size {
+        if !eof {
+            (0, 0, Cut::End)
         } else {
-            if source_size > self.max_size {
-                source_size = self.max_size;
+            (0, source_size, Cut::End)
+        }
+    } else {
+        if source_size > max_size {
+            source_size = max_size;
+        }
+        let source_len1: usize = center_size(avg_size, min_size, source_size);
+        let source_len2: usize = source_size;
+        let mut hash: u32 = 0;
+        let mut source_offset = min_size;
+        // Start by using the "harder" chunking judgement to find chunks
+        // that run smaller than the desired normal size.
+        while source_offset < source_len1 {
+            let index = source[source_offset] as usize;
+            source_offset += 1;
+            hash = (hash >> 1) + TABLE[index];
+            if (hash & mask_s) == 0 {
+                return (hash, source_offset, Cut::Hash);
             }
-            let source_start: usize = source_offset;
-            let source_len1: usize =
-                source_offset + center_size(self.avg_size, self.min_size, source_size);
-            let source_len2: usize = source_offset + source_size;
-            let mut hash: u32 = 0;
-            source_offset += self.min_size;
-            // Start by using the "harder" chunking judgement to find chunks
-            // that run smaller than the desired normal size.
-            while source_offset < source_len1 {
-                let index = self.source[source_offset] as usize;
-                source_offset += 1;
-                hash = (hash >> 1) + TABLE[index];
-                if (hash & self.mask_s) == 0 {
-                    return (hash, source_offset - source_start);
-                }
+        }
+        // Fall back to using the "easier" chunking judgement to find chunks
+        // that run larger than the desired normal size.
+        while source_offset < source_len2 {
+            let index = source[source_offset] as usize;
+            source_offset += 1;
+            hash = (hash >> 1) + TABLE[index];
+            if (hash & mask_l) == 0 {
+                return (hash, source_offset, Cut::Hash);
             }
-            // Fall back to using the "easier" chunking judgement to find chunks
-            // that run larger than the desired normal size.
-            while source_offset < source_len2 {
-                let index = self.source[source_offset] as usize;
-                source_offset += 1;
-                hash = (hash >> 1) + TABLE[index];
-                if (hash & self.mask_l) == 0 {
-                    return (hash, source_offset - source_start);
+        }
+        // If source is not the last buffer, we may yet find a larger chunk.
+        // If sourceSize === maximum, we will not find a larger chunk and should emit.
+        if !eof && source_size < max_size {
+            (hash, 0, Cut::End)
+        } else if source_size == max_size {
+            // All else fails, return the whole chunk. This will happen with
+            // pathological data, such as all zeroes.
+            (hash, source_size, Cut::MaxSize)
+        } else {
+            (hash, source_size, Cut::End)
+        }
+    }
+}
+
+impl Iterator for FastCDC<'_> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        let chunk_start = self.bytes_processed;
+        self.next_cut().map(|(chunk_hash, chunk_end, _)| Chunk {
+            hash: chunk_hash,
+            offset: chunk_start,
+            length: chunk_end - chunk_start,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper_bound = self.bytes_remaining / self.min_size;
+        (1.min(upper_bound), Some(upper_bound))
+    }
+}
+
+impl Chunker for FastCDC<'_> {
+    type Error = Infallible;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Infallible>> {
+        let source = self.source;
+        let offset = self.bytes_processed;
+        self.next_cut().map(|(hash, end, cut)| {
+            let info = ChunkInfo {
+                hash: hash as u64,
+                offset: offset as u64,
+                length: end - offset,
+                cut,
+            };
+            Ok((info, &source[offset..end]))
+        })
+    }
+}
+
+///
+/// The error type returned by the streaming chunkers in this module.
+///
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum Error {
+    /// End of source data reached.
+    Empty,
+    /// An I/O error occurred.
+    IoError(std::io::Error),
+    /// Something unexpected happened.
+    Other(String),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Empty => write!(f, "no more data"),
+            Error::IoError(e) => write!(f, "I/O error: {e}"),
+            Error::Other(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::IoError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IoError(ioerr) => ioerr,
+            Error::Empty => Self::from(std::io::ErrorKind::UnexpectedEof),
+            Error::Other(str) => Self::other(str),
+        }
+    }
+}
+
+///
+/// Represents a chunk returned by the streaming chunkers in this module.
+///
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkData {
+    /// Hash found at this location.
+    pub hash: u32,
+    /// Starting byte position within the source.
+    pub offset: u64,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+    /// Source bytes contained in this chunk.
+    #[cfg_attr(feature = "serde", serde(with = "crate::bytes", default))]
+    pub data: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl AsRef<[u8]> for ChunkData {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+///
+/// The FastCDC chunker implementation by Joran Dirk Greef with streaming
+/// support.
+///
+/// Use `new` to construct an instance, and then iterate over the `ChunkData`s
+/// via the `Iterator` trait.
+///
+/// Note that this struct allocates a `Vec<u8>` of `max_size` bytes to act as a
+/// buffer when reading from the source and finding chunk boundaries. The cut
+/// points are found as if by `FastCDC::with_eof`, where `eof` is `true` only
+/// once the source has been exhausted, so the chunks are identical to those
+/// produced by `FastCDC::new` over the entire contents of the source.
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use fastcdc::ronomon::StreamCDC;
+/// let source = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let chunker = StreamCDC::new(source, 4096, 16384, 65535);
+/// for result in chunker {
+///     let chunk = result.unwrap();
+///     println!("offset={} length={}", chunk.offset, chunk.length);
+/// }
+/// ```
+///
+#[cfg(feature = "std")]
+pub struct StreamCDC<R: Read> {
+    /// Buffer of data from source for finding cut points.
+    buffer: Vec<u8>,
+    /// Maximum capacity of the buffer (always `max_size`).
+    capacity: usize,
+    /// Number of relevant bytes in the `buffer`.
+    length: usize,
+    /// Source from which data is read into `buffer`.
+    source: R,
+    /// Number of bytes read from the source so far.
+    processed: u64,
+    /// True when the source produces no more data.
+    eof: bool,
+    /// Number of bytes at the front of `buffer` belonging to the chunk most
+    /// recently returned by [`Chunker::next_entry`], yet to be discarded.
+    pending: usize,
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u32,
+    mask_l: u32,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> StreamCDC<R> {
+    ///
+    /// Construct a `StreamCDC` that will process bytes from the given source.
+    ///
+    pub fn new(source: R, min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        debug_assert!(min_size >= MINIMUM_MIN);
+        debug_assert!(min_size <= MINIMUM_MAX);
+        debug_assert!(avg_size >= AVERAGE_MIN);
+        debug_assert!(avg_size <= AVERAGE_MAX);
+        debug_assert!(max_size >= MAXIMUM_MIN);
+        debug_assert!(max_size <= MAXIMUM_MAX);
+        let bits = logarithm2(avg_size);
+        let mask_s = mask(bits + 1);
+        let mask_l = mask(bits - 1);
+        Self {
+            buffer: vec![0_u8; max_size],
+            capacity: max_size,
+            length: 0,
+            source,
+            eof: false,
+            pending: 0,
+            processed: 0,
+            min_size,
+            avg_size,
+            max_size,
+            mask_s,
+            mask_l,
+        }
+    }
+
+    ///
+    /// Construct a new `StreamCDC` using the validated parameters of the given
+    /// `ChunkerConfig`.
+    ///
+    /// This implementation always uses one bit of chunk size normalization and
+    /// a fixed gear table, so `ConfigError::Unsupported` is returned if the
+    /// configuration has a normalization level other than `Level1`, or a
+    /// non-zero seed.
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        check_config(config)?;
+        Ok(StreamCDC::new(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+        ))
+    }
+
+    ///
+    /// Compute the digest of each chunk with the given hasher, producing a
+    /// `ChunkData` and digest pair for each chunk.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "blake3")] {
+    /// # use std::fs::File;
+    /// # use fastcdc::ronomon::StreamCDC;
+    /// let source = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let chunker = StreamCDC::new(source, 4096, 16384, 65535);
+    /// for result in chunker.with_hasher(blake3::Hasher::new()) {
+    ///     let (chunk, digest) = result.unwrap();
+    ///     println!("offset={} digest={:02x?}", chunk.offset, digest);
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> Digested<Self, H> {
+        Digested::new(self, hasher)
+    }
+
+    /// Fill the buffer with data from the source, returning the number of bytes
+    /// read (zero if end of source has been reached).
+    fn fill_buffer(&mut self) -> Result<usize, Error> {
+        // this code originally copied from asuran crate
+        if self.eof {
+            Ok(0)
+        } else {
+            let mut all_bytes_read = 0;
+            while !self.eof && self.length < self.capacity {
+                let bytes_read = self.source.read(&mut self.buffer[self.length..])?;
+                if bytes_read == 0 {
+                    self.eof = true;
+                } else {
+                    self.length += bytes_read;
+                    all_bytes_read += bytes_read;
                 }
             }
-            // If source is not the last buffer, we may yet find a larger chunk.
-            // If sourceSize === maximum, we will not find a larger chunk and should emit.
-            if !self.eof && source_size < self.max_size {
-                (hash, 0)
+            Ok(all_bytes_read)
+        }
+    }
+
+    /// Drains a specified number of bytes from the buffer, then resizes the
+    /// buffer back to `capacity` size in preparation for further reads.
+    fn drain_bytes(&mut self, count: usize) -> Result<Vec<u8>, Error> {
+        // this code originally copied from asuran crate
+        if count > self.length {
+            Err(Error::Other(format!(
+                "drain_bytes() called with count larger than length: {} > {}",
+                count, self.length
+            )))
+        } else {
+            let mut data = Vec::with_capacity(count);
+            data.extend_from_slice(&self.buffer[..count]);
+            self.buffer.copy_within(count..self.length, 0);
+            self.length -= count;
+            Ok(data)
+        }
+    }
+
+    /// Discards the bytes of the chunk most recently returned by
+    /// [`Chunker::next_entry`], if any.
+    fn discard_pending(&mut self) {
+        if self.pending > 0 {
+            self.buffer.copy_within(self.pending..self.length, 0);
+            self.length -= self.pending;
+            self.pending = 0;
+        }
+    }
+
+    /// Find the next cut point in the buffer, returning the hash and the
+    /// length of the chunk. If the end of the source has been reached, returns
+    /// `Error::Empty` as the error.
+    fn find_chunk(&mut self) -> Result<(u32, usize, Cut), Error> {
+        self.discard_pending();
+        self.fill_buffer()?;
+        if self.length == 0 {
+            Err(Error::Empty)
+        } else {
+            let (hash, count, cut) = cut(
+                &self.buffer[..self.length],
+                self.min_size,
+                self.avg_size,
+                self.max_size,
+                self.mask_s,
+                self.mask_l,
+                self.eof,
+            );
+            if count == 0 {
+                Err(Error::Empty)
             } else {
-                // All else fails, return the whole chunk. This will happen with
-                // pathological data, such as all zeroes.
-                (hash, source_size)
+                Ok((hash, count, cut))
             }
         }
     }
+
+    /// Find the next chunk in the source. If the end of the source has been
+    /// reached, returns `Error::Empty` as the error.
+    fn read_chunk(&mut self) -> Result<ChunkData, Error> {
+        let (hash, count, _) = self.find_chunk()?;
+        let offset = self.processed;
+        self.processed += count as u64;
+        let data = self.drain_bytes(count)?;
+        Ok(ChunkData {
+            hash,
+            offset,
+            length: count,
+            data,
+        })
+    }
 }
 
-impl Iterator for FastCDC<'_> {
-    type Item = Chunk;
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for StreamCDC<R> {
+    type Item = Result<ChunkData, Error>;
 
-    fn next(&mut self) -> Option<Chunk> {
-        if self.bytes_remaining == 0 {
+    fn next(&mut self) -> Option<Result<ChunkData, Error>> {
+        let slice = self.read_chunk();
+        if let Err(Error::Empty) = slice {
             None
         } else {
-            let (chunk_hash, chunk_size) = self.cut(self.bytes_processed, self.bytes_remaining);
-            if chunk_size == 0 {
-                None
-            } else {
-                let chunk_start = self.bytes_processed;
-                self.bytes_processed += chunk_size;
-                self.bytes_remaining -= chunk_size;
-                Some(Chunk {
-                    hash: chunk_hash,
-                    offset: chunk_start,
-                    length: chunk_size,
-                })
+            Some(slice)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Chunker for StreamCDC<R> {
+    type Error = Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Error>> {
+        match self.find_chunk() {
+            Err(Error::Empty) => None,
+            Err(error) => Some(Err(error)),
+            Ok((hash, count, cut)) => {
+                let offset = self.processed;
+                self.processed += count as u64;
+                self.pending = count;
+                let info = ChunkInfo {
+                    hash: hash as u64,
+                    offset,
+                    length: count,
+                    cut,
+                };
+                Some(Ok((info, &self.buffer[..count])))
             }
         }
     }
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let upper_bound = self.bytes_remaining / self.min_size;
-        (1.min(upper_bound), Some(upper_bound))
+///
+/// Reject the configuration settings that this implementation cannot honor.
+///
+pub(crate) fn check_config(config: &ChunkerConfig) -> Result<(), ConfigError> {
+    if config.normalization() != Normalization::Level1 {
+        return Err(ConfigError::Unsupported(
+            "ronomon only supports normalization level 1",
+        ));
     }
+    if config.seed() != 0 {
+        return Err(ConfigError::Unsupported("ronomon does not support a seed"));
+    }
+    Ok(())
 }
 
 ///
@@ -231,13 +708,6 @@ fn mask(bits: u32) -> u32 {
     (1u32 << bits) - 1
 }
 
-// Rounded base-2 logarithm; matches the behavior pre-4.0.0 so that mask
-// selection picks the bucket whose target chunk size is closest to `value`,
-// rather than always rounding down (which `usize::ilog2` does).
-fn logarithm2(value: usize) -> u32 {
-    (value as f64).log2().round() as u32
-}
-
 //
 // TABLE contains seemingly "random" numbers which are created by ciphering a
 // 1024-byte array of all zeros using a 32-byte key and 16-byte nonce (a.k.a.
@@ -252,7 +722,7 @@ fn logarithm2(value: usize) -> u32 {
 // The program to produce this table is named table32.rs in examples.
 //
 #[rustfmt::skip]
-const TABLE: [u32; 256] = [
+pub(crate) const TABLE: [u32; 256] = [
     0x5c95c078, 0x22408989, 0x2d48a214, 0x12842087, 0x530f8afb, 0x474536b9, 0x2963b4f1, 0x44cb738b,
     0x4ea7403d, 0x4d606b6e, 0x074ec5d3, 0x3af39d18, 0x726003ca, 0x37a62a74, 0x51a2f58e, 0x7506358e,
     0x5d4ab128, 0x4d4ae17b, 0x41e85924, 0x470c36f7, 0x4741cbe1, 0x01bb7f30, 0x617c1de3, 0x2b0c3a1f,
@@ -287,10 +757,11 @@ const TABLE: [u32; 256] = [
     0x2eac53a6, 0x16139e09, 0x0afd0dbc, 0x2a4d4237, 0x56a368c7, 0x234325e4, 0x2dce9187, 0x32e8ea7e
 ];
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::test_util::{Sip, Trickle};
+    use std::fs::{self, File};
 
     #[test]
     fn test_center_size() {
@@ -502,4 +973,180 @@ mod tests {
         assert_eq!(results[1].offset, 32857);
         assert_eq!(results[1].length, 76609);
     }
+
+    #[test]
+    fn test_with_config() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let chunker = FastCDC::with_config(&contents, &config).unwrap();
+        let results: Vec<Chunk> = chunker.collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_with_config_unsupported() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .try_build()
+            .unwrap();
+        let result = FastCDC::with_config(&[], &config);
+        assert!(matches!(result, Err(ConfigError::Unsupported(_))));
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(1)
+            .try_build()
+            .unwrap();
+        let result = FastCDC::with_config(&[], &config);
+        assert!(matches!(result, Err(ConfigError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_error_fmt() {
+        let err = Error::Empty;
+        assert_eq!(format!("{err}"), "no more data");
+    }
+
+    #[test]
+    fn test_cut_points_into() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<(u32, usize)> = FastCDC::new(&contents, 8192, 16384, 32768)
+            .map(|c| (c.hash, c.offset + c.length))
+            .collect();
+        for size in [1, 2, 3, 100] {
+            let mut chunker = FastCDC::new(&contents, 8192, 16384, 32768);
+            let mut points = vec![(0, 0); size];
+            let mut results = Vec::new();
+            loop {
+                let count = chunker.cut_points_into(&mut points);
+                if count == 0 {
+                    break;
+                }
+                results.extend_from_slice(&points[..count]);
+            }
+            assert_eq!(results, expected);
+            assert_eq!(chunker.next(), None);
+        }
+        // picking up where the iterator left off
+        let mut chunker = FastCDC::new(&contents, 8192, 16384, 32768);
+        chunker.next();
+        let mut results = Vec::new();
+        chunker.collect_cut_points(&mut results);
+        assert_eq!(results, expected[1..]);
+        assert_eq!(chunker.cut_points_into(&mut [(0, 0); 4]), 0);
+    }
+
+    #[test]
+    fn test_cut_points_not_eof() {
+        // the tail that may yet be part of a larger chunk is left alone
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<(u32, usize)> = FastCDC::with_eof(&contents, 8192, 16384, 32768, false)
+            .map(|c| (c.hash, c.offset + c.length))
+            .collect();
+        let mut chunker = FastCDC::with_eof(&contents, 8192, 16384, 32768, false);
+        let mut results = Vec::new();
+        chunker.collect_cut_points(&mut results);
+        assert_eq!(results, expected);
+        assert!(*results.last().map(|(_, end)| end).unwrap() < contents.len());
+    }
+
+    fn check_stream<R: Read>(chunker: StreamCDC<R>, contents: &[u8], expected: &[Chunk]) {
+        let mut count = 0;
+        for (result, other) in chunker.zip(expected.iter()) {
+            let chunk = result.unwrap();
+            assert_eq!(chunk.hash, other.hash);
+            assert_eq!(chunk.offset, other.offset as u64);
+            assert_eq!(chunk.length, other.length);
+            assert_eq!(
+                chunk.data,
+                &contents[other.offset..other.offset + other.length]
+            );
+            count += 1;
+        }
+        assert_eq!(count, expected.len());
+    }
+
+    #[test]
+    fn test_stream_sekien_matches_fastcdc() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        for (min_size, avg_size, max_size) in [
+            (4096, 16384, 65535),
+            (8192, 16384, 32768),
+            (16384, 32768, 65536),
+            (32768, 65536, 131_072),
+            (64, 256, 1024),
+        ] {
+            let expected: Vec<Chunk> =
+                FastCDC::new(&contents, min_size, avg_size, max_size).collect();
+            let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+            let chunker = StreamCDC::new(file, min_size, avg_size, max_size);
+            check_stream(chunker, &contents, &expected);
+            let chunker = StreamCDC::new(Trickle(&contents), min_size, avg_size, max_size);
+            check_stream(chunker, &contents, &expected);
+        }
+    }
+
+    #[test]
+    fn test_stream_tail_at_eof() {
+        // a tail shorter than the maximum, but longer than the minimum, where
+        // the center size is computed from the remaining bytes
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        for length in [1000, 8192, 8193, 20000, 40000, 65537] {
+            let slice = &contents[..length];
+            let expected: Vec<Chunk> = FastCDC::new(slice, 8192, 16384, 32768).collect();
+            let chunker = StreamCDC::new(slice, 8192, 16384, 32768);
+            check_stream(chunker, slice, &expected);
+        }
+        let zeros = vec![0u8; 100_000];
+        let expected: Vec<Chunk> = FastCDC::new(&zeros, 8192, 16384, 32768).collect();
+        assert_eq!(expected.len(), 4);
+        check_stream(
+            StreamCDC::new(zeros.as_slice(), 8192, 16384, 32768),
+            &zeros,
+            &expected,
+        );
+    }
+
+    #[test]
+    fn test_stream_with_config_unsupported() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(42)
+            .try_build()
+            .unwrap();
+        let result = StreamCDC::with_config([].as_slice(), &config);
+        assert!(matches!(result, Err(ConfigError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_with_hasher() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let results: Vec<_> = chunker.with_hasher(Sip::default()).collect();
+        assert_eq!(results.len(), expected.len());
+        for ((chunk, digest), other) in results.iter().zip(&expected) {
+            assert_eq!(chunk, other);
+            let data = &contents[chunk.offset..chunk.offset + chunk.length];
+            assert_eq!(*digest, Sip::default().digest(data));
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_stream_with_hasher() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::new(file, 4096, 16384, 65535);
+        let mut index = 0;
+        for result in chunker.with_hasher(blake3::Hasher::new()) {
+            let (chunk, digest) = result.unwrap();
+            assert_eq!(chunk.offset as usize, expected[index].offset);
+            assert_eq!(chunk.length, expected[index].length);
+            assert_eq!(&digest, blake3::hash(&chunk.data).as_bytes());
+            index += 1;
+        }
+        assert_eq!(index, expected.len());
+    }
 }
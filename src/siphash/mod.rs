@@ -0,0 +1,92 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! A minimal implementation of the SipHash-2-4 pseudorandom function, as
+//! described by Jean-Philippe Aumasson and Daniel J. Bernstein in
+//! [SipHash: a fast short-input PRF](https://www.aumasson.jp/siphash/siphash.pdf).
+//! It is used for deriving gear tables from a secret key, and is not exposed
+//! outside of this crate.
+
+///
+/// Compute the SipHash-2-4 of the message using the given 128-bit key.
+///
+pub(crate) fn siphash24(key: &[u8; 16], message: &[u8]) -> u64 {
+    let k0 = u64::from_le_bytes(key[..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(key[8..].try_into().unwrap());
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+    let mut blocks = message.chunks_exact(8);
+    for block in &mut blocks {
+        compress(&mut v, u64::from_le_bytes(block.try_into().unwrap()));
+    }
+    // the final block holds the remaining bytes and the length of the message
+    let mut last = (message.len() as u64) << 56;
+    for (index, byte) in blocks.remainder().iter().enumerate() {
+        last |= (*byte as u64) << (8 * index);
+    }
+    compress(&mut v, last);
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+// Absorb one 64-bit word of the message with two rounds.
+fn compress(v: &mut [u64; 4], m: u64) {
+    v[3] ^= m;
+    round(v);
+    round(v);
+    v[0] ^= m;
+}
+
+fn round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vectors() {
+        // from vectors.h of the reference implementation, in which the key is
+        // the bytes 0 through 15 and the message is the bytes 0 through n-1
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let message: Vec<u8> = (0..64).collect();
+        assert_eq!(siphash24(&key, &message[..0]), 0x726fdb47dd0e0e31);
+        assert_eq!(siphash24(&key, &message[..1]), 0x74f839c593dc67fd);
+        assert_eq!(siphash24(&key, &message[..8]), 0x93f5f5799a932462);
+        assert_eq!(siphash24(&key, &message[..15]), 0xa129ca6149be45e5);
+        assert_eq!(siphash24(&key, &message[..63]), 0x958a324ceb064572);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_matches_std() {
+        use std::hash::{Hasher, SipHasher};
+        let key: [u8; 16] = core::array::from_fn(|i| (i as u8).wrapping_mul(37));
+        let k0 = u64::from_le_bytes(key[..8].try_into().unwrap());
+        let k1 = u64::from_le_bytes(key[8..].try_into().unwrap());
+        let message: Vec<u8> = (0..100u8).map(|b| b.wrapping_mul(91)).collect();
+        for length in 0..message.len() {
+            let mut hasher = SipHasher::new_with_keys(k0, k1);
+            hasher.write(&message[..length]);
+            assert_eq!(siphash24(&key, &message[..length]), hasher.finish());
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`DedupStats`], which collects statistics about the
+//! chunks produced by any of the chunkers, identified by their digests, such as
+//! to compare the deduplication achieved by different chunking parameters on a
+//! sample of a dataset.
+//!
+//! ```
+//! # #[cfg(feature = "blake3")] {
+//! use fastcdc::stats::DedupStats;
+//! use fastcdc::v2020::FastCDC;
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let mut stats = DedupStats::new();
+//! let mut hasher = blake3::Hasher::new();
+//! // the same file twice, as if stored twice
+//! for _ in 0..2 {
+//!     let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+//!     stats.add_chunker(chunker, &mut hasher).unwrap();
+//! }
+//! assert_eq!(stats.total_bytes(), 2 * contents.len() as u64);
+//! assert_eq!(stats.unique_bytes(), contents.len() as u64);
+//! assert_eq!(stats.dedup_ratio(), 2.0);
+//! println!("{stats}");
+//! # }
+//! ```
+use crate::chunker::{ChunkInfo, Chunker, Cut};
+use crate::digest::ChunkHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+///
+/// Collects statistics about a sequence of chunks and their digests.
+///
+/// A chunk whose digest has been seen before is counted as a duplicate, which
+/// adds to the total bytes but not to the unique bytes. The statistics of the
+/// chunk lengths cover all of the chunks, including the duplicates.
+///
+#[derive(Debug, Clone)]
+pub struct DedupStats<D> {
+    seen: HashSet<D>,
+    chunk_count: u64,
+    total_bytes: u64,
+    unique_bytes: u64,
+    max_size_cuts: u64,
+    min_length: usize,
+    max_length: usize,
+    // sum of the squares of the lengths, for the standard deviation
+    sum_squares: u128,
+    // number of chunks by the base-2 logarithm of their length
+    histogram: [u64; 64],
+}
+
+impl<D: Eq + Hash> DedupStats<D> {
+    /// Construct an empty [`DedupStats`].
+    pub fn new() -> Self {
+        Self {
+            seen: HashSet::new(),
+            chunk_count: 0,
+            total_bytes: 0,
+            unique_bytes: 0,
+            max_size_cuts: 0,
+            min_length: 0,
+            max_length: 0,
+            sum_squares: 0,
+            histogram: [0; 64],
+        }
+    }
+
+    ///
+    /// Record a chunk described by a chunker, with its digest, returning `true`
+    /// if the digest had not been seen before.
+    ///
+    pub fn add(&mut self, info: &ChunkInfo, digest: D) -> bool {
+        let length = info.length;
+        if self.chunk_count == 0 || length < self.min_length {
+            self.min_length = length;
+        }
+        self.max_length = self.max_length.max(length);
+        self.chunk_count += 1;
+        self.total_bytes += length as u64;
+        self.sum_squares += (length as u128) * (length as u128);
+        self.histogram[length.max(1).ilog2() as usize] += 1;
+        if info.cut == Cut::MaxSize {
+            self.max_size_cuts += 1;
+        }
+        let unique = self.seen.insert(digest);
+        if unique {
+            self.unique_bytes += length as u64;
+        }
+        unique
+    }
+
+    ///
+    /// Record all of the chunks of the given chunker, computing the digest of
+    /// each with the given hasher.
+    ///
+    pub fn add_chunker<C, H>(&mut self, mut chunker: C, hasher: &mut H) -> Result<(), C::Error>
+    where
+        C: Chunker,
+        H: ChunkHasher<Digest = D>,
+    {
+        while let Some(result) = chunker.next_entry() {
+            let (info, data) = result?;
+            let digest = hasher.digest(data);
+            self.add(&info, digest);
+        }
+        Ok(())
+    }
+
+    /// Number of chunks recorded, including the duplicates.
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_count
+    }
+
+    /// Number of distinct chunks recorded.
+    pub fn unique_chunks(&self) -> u64 {
+        self.seen.len() as u64
+    }
+
+    /// Sum of the lengths of all of the chunks.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Sum of the lengths of the distinct chunks, which is what needs storing.
+    pub fn unique_bytes(&self) -> u64 {
+        self.unique_bytes
+    }
+
+    ///
+    /// The ratio of the total bytes to the unique bytes, which is 1.0 without
+    /// any duplicates, and 1.0 as well when nothing has been recorded.
+    ///
+    pub fn dedup_ratio(&self) -> f64 {
+        if self.unique_bytes == 0 {
+            1.0
+        } else {
+            self.total_bytes as f64 / self.unique_bytes as f64
+        }
+    }
+
+    ///
+    /// Number of chunks that the chunker cut at the maximum size because the
+    /// hash did not match before then, as reported by [`ChunkInfo::cut`].
+    ///
+    pub fn max_size_cuts(&self) -> u64 {
+        self.max_size_cuts
+    }
+
+    /// Length of the smallest chunk, or zero if there are none.
+    pub fn min_length(&self) -> usize {
+        self.min_length
+    }
+
+    /// Length of the largest chunk, or zero if there are none.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    /// Mean length of the chunks, or zero if there are none.
+    pub fn mean_length(&self) -> f64 {
+        if self.chunk_count == 0 {
+            0.0
+        } else {
+            self.total_bytes as f64 / self.chunk_count as f64
+        }
+    }
+
+    /// Population standard deviation of the chunk lengths.
+    pub fn stddev_length(&self) -> f64 {
+        if self.chunk_count == 0 {
+            return 0.0;
+        }
+        let count = self.chunk_count as f64;
+        let mean = self.mean_length();
+        let variance = self.sum_squares as f64 / count - mean * mean;
+        variance.max(0.0).sqrt()
+    }
+
+    ///
+    /// Histogram of the chunk lengths in buckets of powers of two, yielding the
+    /// smallest length of each non-empty bucket and its number of chunks, in
+    /// increasing order of length.
+    ///
+    pub fn histogram(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bits, count)| (1 << bits, *count))
+    }
+}
+
+impl<D: Eq + Hash> Default for DedupStats<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: Eq + Hash> Extend<(ChunkInfo, D)> for DedupStats<D> {
+    fn extend<I: IntoIterator<Item = (ChunkInfo, D)>>(&mut self, iter: I) {
+        for (info, digest) in iter {
+            self.add(&info, digest);
+        }
+    }
+}
+
+impl<D: Eq + Hash> fmt::Display for DedupStats<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "chunks: {} ({} unique)",
+            self.chunk_count,
+            self.unique_chunks()
+        )?;
+        writeln!(
+            f,
+            "bytes: {} ({} unique), dedup ratio {:.3}",
+            self.total_bytes,
+            self.unique_bytes,
+            self.dedup_ratio()
+        )?;
+        writeln!(
+            f,
+            "length: min {}, max {}, mean {:.1}, stddev {:.1}",
+            self.min_length,
+            self.max_length,
+            self.mean_length(),
+            self.stddev_length()
+        )?;
+        write!(f, "cut at max_size: {}", self.max_size_cuts)?;
+        for (length, count) in self.histogram() {
+            write!(f, "\n  >= {length}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Fnv;
+    use crate::{ronomon, v2020};
+    use std::fs;
+
+    fn info(length: usize, cut: Cut) -> ChunkInfo {
+        ChunkInfo {
+            hash: 0,
+            offset: 0,
+            length,
+            cut,
+        }
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats: DedupStats<u64> = DedupStats::new();
+        assert_eq!(stats.chunk_count(), 0);
+        assert_eq!(stats.dedup_ratio(), 1.0);
+        assert_eq!(stats.min_length(), 0);
+        assert_eq!(stats.mean_length(), 0.0);
+        assert_eq!(stats.stddev_length(), 0.0);
+        assert_eq!(stats.histogram().count(), 0);
+    }
+
+    #[test]
+    fn test_stats_add() {
+        let mut stats = DedupStats::new();
+        assert!(stats.add(&info(1024, Cut::MaxSize), 1));
+        assert!(stats.add(&info(512, Cut::Hash), 2));
+        assert!(!stats.add(&info(1024, Cut::MaxSize), 1));
+        // the hash matching at the maximum size is not a cut by the maximum size
+        assert!(stats.add(&info(1024, Cut::Hash), 3));
+        assert!(stats.add(&info(600, Cut::End), 4));
+        assert_eq!(stats.chunk_count(), 5);
+        assert_eq!(stats.unique_chunks(), 4);
+        assert_eq!(stats.total_bytes(), 4184);
+        assert_eq!(stats.unique_bytes(), 3160);
+        assert_eq!(stats.dedup_ratio(), 4184.0 / 3160.0);
+        assert_eq!(stats.max_size_cuts(), 2);
+        assert_eq!(stats.min_length(), 512);
+        assert_eq!(stats.max_length(), 1024);
+        assert_eq!(stats.mean_length(), 836.8);
+        // deviations of 187.2 (three times), -324.8, and -236.8
+        let expected =
+            ((187.2f64.powi(2) * 3.0 + 324.8f64.powi(2) + 236.8f64.powi(2)) / 5.0).sqrt();
+        assert!((stats.stddev_length() - expected).abs() < 1e-9);
+        let histogram: Vec<_> = stats.histogram().collect();
+        assert_eq!(histogram, vec![(512, 2), (1024, 3)]);
+        let report = stats.to_string();
+        assert!(report.contains("dedup ratio 1.324"));
+        assert!(report.contains("cut at max_size: 2"));
+    }
+
+    #[test]
+    fn test_stats_chunkers() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut stats = DedupStats::new();
+        let chunker = ronomon::FastCDC::new(&contents, 8192, 16384, 32768);
+        stats.add_chunker(chunker, &mut Fnv::default()).unwrap();
+        assert_eq!(stats.chunk_count(), 6);
+        assert_eq!(stats.total_bytes(), contents.len() as u64);
+        assert_eq!(stats.unique_bytes(), contents.len() as u64);
+        // the file again, from a stream, is entirely duplicate
+        let chunker = ronomon::StreamCDC::new(contents.as_slice(), 8192, 16384, 32768);
+        stats.add_chunker(chunker, &mut Fnv::default()).unwrap();
+        assert_eq!(stats.unique_chunks(), 6);
+        assert_eq!(stats.dedup_ratio(), 2.0);
+        let total: u64 = stats.histogram().map(|(_, count)| count).sum();
+        assert_eq!(total, 12);
+
+        let mut stats = DedupStats::new();
+        let chunker = v2020::FastCDC::new(&contents, 4096, 16384, 65535);
+        stats.add_chunker(chunker, &mut Fnv::default()).unwrap();
+        assert_eq!(stats.chunk_count(), 5);
+        assert_eq!(stats.max_size_cuts(), 0);
+        assert_eq!(stats.total_bytes(), contents.len() as u64);
+
+        // nothing but cuts at the maximum size, except for the remainder
+        let zeros = vec![0u8; 10_000];
+        let mut stats = DedupStats::new();
+        let chunker = v2020::StreamCDC::new(zeros.as_slice(), 64, 256, 1024);
+        stats.add_chunker(chunker, &mut Fnv::default()).unwrap();
+        assert_eq!(stats.chunk_count(), 10);
+        assert_eq!(stats.unique_chunks(), 2);
+        assert_eq!(stats.max_size_cuts(), 9);
+    }
+}
@@ -0,0 +1,79 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! Helpers shared by the tests of the other modules.
+use crate::digest::ChunkHasher;
+use crate::fingerprint::fnv1a;
+use std::io::Read;
+
+///
+/// 64-bit FNV-1a, which is plenty to tell the chunks of the fixtures apart.
+///
+#[derive(Default)]
+pub(crate) struct Fnv(Vec<u8>);
+
+impl ChunkHasher for Fnv {
+    type Digest = [u8; 8];
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 8] {
+        fnv1a(std::mem::take(&mut self.0).into_iter()).to_le_bytes()
+    }
+}
+
+///
+/// 128-bit digest of two SipHash values, for when the digests must not
+/// collide.
+///
+#[derive(Default)]
+pub(crate) struct Sip(Vec<u8>);
+
+impl ChunkHasher for Sip {
+    type Digest = [u8; 16];
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data);
+    }
+
+    fn finalize_reset(&mut self) -> [u8; 16] {
+        let first = crate::siphash::siphash24(&[1; 16], &self.0);
+        let second = crate::siphash::siphash24(&[2; 16], &self.0);
+        self.0.clear();
+        let mut digest = [0; 16];
+        digest[..8].copy_from_slice(&first.to_le_bytes());
+        digest[8..].copy_from_slice(&second.to_le_bytes());
+        digest
+    }
+}
+
+///
+/// Reader that returns at most a few bytes per call to `read`.
+///
+pub(crate) struct Trickle<'a>(pub(crate) &'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = buf.len().min(self.0.len()).min(7);
+        buf[..count].copy_from_slice(&self.0[..count]);
+        self.0 = &self.0[count..];
+        Ok(count)
+    }
+}
+
+///
+/// Deterministic pseudorandom bytes (xorshift64*).
+///
+pub(crate) fn noise(length: usize, mut state: u64) -> Vec<u8> {
+    (0..length)
+        .map(|_| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 56) as u8
+        })
+        .collect()
+}
@@ -0,0 +1,395 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! This module defines [`Tuner`], which chunks sample data with the
+//! [`v2020`](crate::v2020) chunker over a grid of average sizes, ratios of the
+//! minimum and maximum sizes to the average, and normalization levels, and
+//! reports the deduplication ratio, metadata overhead, and throughput of each
+//! configuration, recommending one for a target average chunk size.
+//!
+//! Smaller chunks find more duplicate data but need more metadata, one
+//! manifest entry per chunk, while a wider range of sizes and less
+//! normalization lets the chunk boundaries follow the content more closely at
+//! the cost of a greater variance in the chunk sizes. The recommendation is the
+//! configuration, among those with the average size closest to the target,
+//! that minimizes the unique bytes plus the size of the manifest entries.
+//!
+//! ```
+//! # #[cfg(feature = "blake3")] {
+//! use fastcdc::tuner::Tuner;
+//!
+//! let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+//! let report = Tuner::new(8192).run(&[&contents], &mut blake3::Hasher::new());
+//! let best = report.recommendation().unwrap();
+//! assert_eq!(best.config.avg_size(), 8192);
+//! println!("{report}");
+//! # }
+//! ```
+use crate::chunker::Chunker;
+use crate::config::{ChunkerConfig, Normalization};
+use crate::digest::ChunkHasher;
+use crate::manifest::ENTRY_LEN;
+use crate::stats::DedupStats;
+use crate::v2020::FastCDC;
+use std::fmt;
+use std::hash::Hash;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+///
+/// The minimum and maximum chunk sizes relative to the average size, as in the
+/// `avg / 4` and `avg * 4` used by the examples.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SizeRatio {
+    /// The average size divided by this value is the minimum size.
+    pub min_divisor: usize,
+    /// The average size multiplied by this value is the maximum size.
+    pub max_multiplier: usize,
+}
+
+impl SizeRatio {
+    /// Create a ratio with the given divisor and multiplier.
+    pub fn new(min_divisor: usize, max_multiplier: usize) -> Self {
+        Self {
+            min_divisor,
+            max_multiplier,
+        }
+    }
+}
+
+///
+/// Runs the [`v2020`](crate::v2020) chunker over a grid of configurations.
+///
+/// By default, the grid covers the average sizes from a quarter of the target
+/// to four times the target, in powers of two, the ratios 2/2, 4/4, 2/8, and
+/// 8/8, and all of the normalization levels. Configurations that fail
+/// validation, such as a minimum size below
+/// [`MINIMUM_MIN`](crate::v2020::MINIMUM_MIN), are left out.
+///
+#[derive(Debug, Clone)]
+pub struct Tuner {
+    target_avg: usize,
+    avg_sizes: Vec<usize>,
+    ratios: Vec<SizeRatio>,
+    levels: Vec<Normalization>,
+}
+
+impl Tuner {
+    ///
+    /// Construct a [`Tuner`] with the default grid around the given target
+    /// average chunk size.
+    ///
+    pub fn new(target_avg: usize) -> Self {
+        Self {
+            target_avg,
+            avg_sizes: vec![
+                target_avg / 4,
+                target_avg / 2,
+                target_avg,
+                target_avg * 2,
+                target_avg * 4,
+            ],
+            ratios: vec![
+                SizeRatio::new(2, 2),
+                SizeRatio::new(4, 4),
+                SizeRatio::new(2, 8),
+                SizeRatio::new(8, 8),
+            ],
+            levels: vec![
+                Normalization::Level0,
+                Normalization::Level1,
+                Normalization::Level2,
+                Normalization::Level3,
+            ],
+        }
+    }
+
+    /// Set the average sizes of the grid.
+    pub fn avg_sizes(mut self, avg_sizes: Vec<usize>) -> Self {
+        self.avg_sizes = avg_sizes;
+        self
+    }
+
+    /// Set the ratios of the minimum and maximum sizes of the grid.
+    pub fn ratios(mut self, ratios: Vec<SizeRatio>) -> Self {
+        self.ratios = ratios;
+        self
+    }
+
+    /// Set the normalization levels of the grid.
+    pub fn levels(mut self, levels: Vec<Normalization>) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// The valid configurations of the grid, in the order they are run.
+    pub fn configs(&self) -> Vec<ChunkerConfig> {
+        let mut configs = Vec::new();
+        for avg_size in &self.avg_sizes {
+            for ratio in &self.ratios {
+                for level in &self.levels {
+                    let min_size = avg_size / ratio.min_divisor.max(1);
+                    let max_size = avg_size.saturating_mul(ratio.max_multiplier);
+                    let result = ChunkerConfig::builder(min_size, *avg_size, max_size)
+                        .normalization(*level)
+                        .try_build();
+                    if let Ok(config) = result {
+                        configs.push(config);
+                    }
+                }
+            }
+        }
+        configs
+    }
+
+    ///
+    /// Chunk all of the samples with each configuration of the grid, as if they
+    /// were stored in the same repository, computing the digest of each chunk
+    /// with the given hasher to find the duplicates.
+    ///
+    /// The throughput covers only finding the chunks, not computing their
+    /// digests. The samples are chunked once beforehand, so that the first
+    /// configuration does not bear the cost of faulting in their pages.
+    ///
+    pub fn run<H>(&self, samples: &[&[u8]], hasher: &mut H) -> TuningReport
+    where
+        H: ChunkHasher,
+        H::Digest: AsRef<[u8]> + Eq + Hash,
+    {
+        let configs = self.configs();
+        if let Some(config) = configs.first() {
+            for sample in samples {
+                let chunker = FastCDC::with_config(sample, config).unwrap();
+                black_box(chunker.count());
+            }
+        }
+        let mut results = Vec::new();
+        for config in configs {
+            let mut elapsed = Duration::ZERO;
+            let mut stats = DedupStats::new();
+            let mut digest_len = 0;
+            for sample in samples {
+                let mut hashing = Duration::ZERO;
+                let start = Instant::now();
+                let mut chunker = FastCDC::with_config(sample, &config).unwrap();
+                while let Some(Ok((info, data))) = chunker.next_entry() {
+                    let hash_start = Instant::now();
+                    let digest = hasher.digest(data);
+                    digest_len = digest.as_ref().len();
+                    stats.add(&info, digest);
+                    hashing += hash_start.elapsed();
+                }
+                elapsed += start.elapsed().saturating_sub(hashing);
+            }
+            let metadata_bytes = stats.chunk_count() * (ENTRY_LEN + digest_len) as u64;
+            let seconds = elapsed.as_secs_f64();
+            results.push(TuningResult {
+                config,
+                chunk_count: stats.chunk_count(),
+                total_bytes: stats.total_bytes(),
+                unique_bytes: stats.unique_bytes(),
+                dedup_ratio: stats.dedup_ratio(),
+                mean_length: stats.mean_length(),
+                stddev_length: stats.stddev_length(),
+                max_size_cuts: stats.max_size_cuts(),
+                metadata_bytes,
+                throughput: if seconds > 0.0 {
+                    stats.total_bytes() as f64 / seconds
+                } else {
+                    f64::INFINITY
+                },
+            });
+        }
+        let recommended = recommend(&results, self.target_avg);
+        TuningReport {
+            results,
+            recommended,
+        }
+    }
+}
+
+///
+/// The outcome of chunking the samples with one configuration.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningResult {
+    /// The configuration used to chunk the samples.
+    pub config: ChunkerConfig,
+    /// Number of chunks, including the duplicates.
+    pub chunk_count: u64,
+    /// Total bytes of the samples.
+    pub total_bytes: u64,
+    /// Bytes of the distinct chunks.
+    pub unique_bytes: u64,
+    /// Ratio of the total bytes to the unique bytes.
+    pub dedup_ratio: f64,
+    /// Mean length of the chunks.
+    pub mean_length: f64,
+    /// Standard deviation of the chunk lengths.
+    pub stddev_length: f64,
+    /// Number of chunks cut at the maximum size, as counted by
+    /// [`DedupStats::max_size_cuts()`].
+    pub max_size_cuts: u64,
+    /// Size of the manifest entries of all of the chunks, as written by
+    /// [`ManifestWriter`](crate::manifest::ManifestWriter).
+    pub metadata_bytes: u64,
+    /// Bytes per second of finding the chunks.
+    pub throughput: f64,
+}
+
+impl TuningResult {
+    /// Metadata bytes as a fraction of the total bytes.
+    pub fn metadata_overhead(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.metadata_bytes as f64 / self.total_bytes as f64
+        }
+    }
+
+    /// Bytes needed to store the samples: the unique chunks and the metadata.
+    pub fn stored_bytes(&self) -> u64 {
+        self.unique_bytes + self.metadata_bytes
+    }
+}
+
+///
+/// The results of [`Tuner::run()`], one for each configuration of the grid.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningReport {
+    /// Results in the order of [`Tuner::configs()`].
+    pub results: Vec<TuningResult>,
+    recommended: Option<usize>,
+}
+
+impl TuningReport {
+    /// The recommended configuration, or `None` if there were no results.
+    pub fn recommendation(&self) -> Option<&TuningResult> {
+        self.recommended.map(|index| &self.results[index])
+    }
+}
+
+impl fmt::Display for TuningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  {:>8} {:>8} {:>8} {:>2} {:>8} {:>7} {:>9} {:>9} {:>8}",
+            "min", "avg", "max", "nc", "chunks", "dedup", "stddev", "overhead", "MiB/s"
+        )?;
+        for (index, result) in self.results.iter().enumerate() {
+            let marker = if Some(index) == self.recommended {
+                '*'
+            } else {
+                ' '
+            };
+            write!(
+                f,
+                "\n{} {:>8} {:>8} {:>8} {:>2} {:>8} {:>7.4} {:>9.1} {:>8.4}% {:>8.1}",
+                marker,
+                result.config.min_size(),
+                result.config.avg_size(),
+                result.config.max_size(),
+                result.config.normalization(),
+                result.chunk_count,
+                result.dedup_ratio,
+                result.stddev_length,
+                result.metadata_overhead() * 100.0,
+                result.throughput / 1_048_576.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Among the results with the average size closest to the target, pick the one
+// that needs the fewest bytes of storage, then the one with the least variance
+// in the chunk sizes.
+fn recommend(results: &[TuningResult], target_avg: usize) -> Option<usize> {
+    let closest = results
+        .iter()
+        .map(|r| r.config.avg_size().abs_diff(target_avg))
+        .min()?;
+    results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.config.avg_size().abs_diff(target_avg) == closest)
+        .min_by(|(_, a), (_, b)| {
+            a.stored_bytes()
+                .cmp(&b.stored_bytes())
+                .then(a.stddev_length.total_cmp(&b.stddev_length))
+        })
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Fnv;
+    use std::fs;
+
+    #[test]
+    fn test_tuner_configs() {
+        let tuner = Tuner::new(16384);
+        let configs = tuner.configs();
+        assert_eq!(configs.len(), 5 * 4 * 4);
+        assert_eq!(configs[0].min_size(), 2048);
+        assert_eq!(configs[0].avg_size(), 4096);
+        assert_eq!(configs[0].max_size(), 8192);
+        assert_eq!(configs[0].normalization(), Normalization::Level0);
+        // invalid configurations are left out
+        let tuner = Tuner::new(256).ratios(vec![SizeRatio::new(8, 2)]);
+        let sizes: Vec<_> = tuner.configs().iter().map(|c| c.avg_size()).collect();
+        assert_eq!(sizes, vec![512, 512, 512, 512, 1024, 1024, 1024, 1024]);
+        let tuner = Tuner::new(8192).levels(vec![Normalization::Level2]);
+        assert!(
+            tuner
+                .configs()
+                .iter()
+                .all(|c| c.normalization() == Normalization::Level2)
+        );
+        assert!(Tuner::new(8192).avg_sizes(vec![]).configs().is_empty());
+    }
+
+    #[test]
+    fn test_tuner_run() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let tuner = Tuner::new(8192)
+            .avg_sizes(vec![4096, 8192, 16384])
+            .ratios(vec![SizeRatio::new(4, 4)]);
+        let mut hasher = Fnv::default();
+        // the file twice, and the second half of it again
+        let samples = [&contents[..], &contents[..], &contents[50_000..]];
+        let report = tuner.run(&samples, &mut hasher);
+        assert_eq!(report.results.len(), 12);
+        let total = (2 * contents.len() + contents.len() - 50_000) as u64;
+        for result in &report.results {
+            assert_eq!(result.total_bytes, total);
+            // the second copy is entirely duplicate
+            assert!(result.unique_bytes >= contents.len() as u64);
+            assert!(result.unique_bytes <= total - contents.len() as u64);
+            assert!(result.dedup_ratio > 1.6);
+            assert_eq!(result.metadata_bytes, result.chunk_count * 20);
+            assert!(result.throughput > 0.0);
+        }
+        let best = report.recommendation().unwrap();
+        assert_eq!(best.config.avg_size(), 8192);
+        let mut others = report
+            .results
+            .iter()
+            .filter(|r| r.config.avg_size() == 8192);
+        assert!(others.all(|r| r.stored_bytes() >= best.stored_bytes()));
+        let text = report.to_string();
+        assert_eq!(text.lines().count(), 13);
+        assert_eq!(text.lines().filter(|l| l.starts_with('*')).count(), 1);
+        assert!(
+            tuner
+                .levels(vec![])
+                .run(&samples, &mut hasher)
+                .recommendation()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+use crate::async_source::{AsyncBuffer, AsyncSource, async_streams};
+
+///
+/// An async-streamable version of the FastCDC chunker implementation from 2016
+/// with streaming support.
+///
+/// Use `new` to construct an instance, and then
+/// [`as_tokio_stream`](AsyncStreamCDC::as_tokio_stream) or
+/// [`as_futures_stream`](AsyncStreamCDC::as_futures_stream) to produce an async
+/// `Stream` of the chunks, depending on whether the source implements the
+/// `AsyncRead` trait from `tokio` or from `futures`. Those streams borrow the
+/// chunker; to produce a `Send + 'static` stream that owns the chunker, such as
+/// for moving into a spawned task, use
+/// [`into_tokio_stream`](AsyncStreamCDC::into_tokio_stream) or
+/// [`into_futures_stream`](AsyncStreamCDC::into_futures_stream) instead.
+///
+/// Both `tokio` and `futures`-based `AsyncRead` inputs are supported via
+/// feature flags, and both features may be enabled at the same time. The
+/// [`as_stream`](AsyncStreamCDC::as_stream) and
+/// [`into_stream`](AsyncStreamCDC::into_stream) methods accept either kind of
+/// source, as described by [`AsyncSource`]. If necessary you can also use the
+/// [`async_compat`](https://docs.rs/async-compat/latest/async_compat/) crate to
+/// adapt your inputs as circumstances may require.
+///
+/// Note that this struct allocates a [`Vec<u8>`] of `max_size` bytes to act as
+/// a buffer when reading from the source and finding chunk boundaries.
+///
+/// ```no_run
+/// # use fastcdc::v2016::AsyncStreamCDC;
+/// # #[cfg(feature = "tokio")]
+/// async fn run() {
+///     use tokio_stream::StreamExt;
+///     let source = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+///     let mut chunker = AsyncStreamCDC::new(source.as_ref(), 4096, 16384, 65535);
+///     let stream = chunker.as_tokio_stream();
+///
+///     let chunks = stream.collect::<Vec<_>>().await;
+///
+///     for result in chunks {
+///         let chunk = result.unwrap();
+///         println!("offset={} length={}", chunk.offset, chunk.length);
+///     }
+/// }
+/// ```
+///
+pub struct AsyncStreamCDC<R> {
+    /// Buffer of data from source for finding cut points.
+    buffer: AsyncBuffer<R>,
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+    gear: Table,
+}
+
+impl<R> AsyncStreamCDC<R> {
+    ///
+    /// Construct a [`AsyncStreamCDC`] that will process bytes from the given
+    /// source.
+    ///
+    /// Uses chunk size normalization level 1 by default.
+    ///
+    pub fn new(source: R, min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::with_level(source, min_size, avg_size, max_size, Normalization::Level1)
+    }
+
+    ///
+    /// Create a new [`AsyncStreamCDC`] with the given normalization level.
+    ///
+    pub fn with_level(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+    ) -> Self {
+        Self::with_level_and_seed(source, min_size, avg_size, max_size, level, 0)
+    }
+
+    ///
+    /// Create a new [`AsyncStreamCDC`] with the given normalization level and
+    /// seed to be XOR'd with the values in the gear table.
+    ///
+    pub fn with_level_and_seed(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        seed: u64,
+    ) -> Self {
+        debug_assert!(min_size >= MINIMUM_MIN);
+        debug_assert!(min_size <= MINIMUM_MAX);
+        debug_assert!(avg_size >= AVERAGE_MIN);
+        debug_assert!(avg_size <= AVERAGE_MAX);
+        debug_assert!(max_size >= MAXIMUM_MIN);
+        debug_assert!(max_size <= MAXIMUM_MAX);
+        let bits = logarithm2(avg_size);
+        let normalization = level.bits();
+        let mask_s = MASKS[(bits + normalization) as usize];
+        let mask_l = MASKS[(bits - normalization) as usize];
+        Self {
+            buffer: AsyncBuffer::new(source, max_size),
+            min_size,
+            avg_size,
+            max_size,
+            mask_s,
+            mask_l,
+            gear: gear_with_seed(seed),
+        }
+    }
+
+    ///
+    /// Create a new [`AsyncStreamCDC`] with the given normalization level and
+    /// gear table, in place of the built-in table.
+    ///
+    pub fn with_level_and_gear(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        Self {
+            gear: Table::generated(*gear.values()),
+            ..Self::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`AsyncStreamCDC`] using the validated parameters of the
+    /// given [`ChunkerConfig`].
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(Self::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
+    /// Find the next chunk in the source, returning `None` if the end of the
+    /// source has been reached.
+    async fn read_chunk<B>(&mut self) -> Option<Result<ChunkData, Error>>
+    where
+        R: AsyncSource<B>,
+    {
+        if let Err(error) = self.buffer.fill_buffer().await {
+            return Some(Err(error.into()));
+        }
+        if self.buffer.data().is_empty() {
+            return None;
+        }
+        let (hash, count) = cut_gear(
+            self.buffer.data(),
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.mask_s,
+            self.mask_l,
+            &self.gear,
+        );
+        if count == 0 {
+            None
+        } else {
+            let (offset, data) = self.buffer.drain_bytes(count);
+            Some(Ok(ChunkData {
+                hash,
+                offset,
+                length: count,
+                data,
+            }))
+        }
+    }
+}
+
+async_streams!();
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncStreamCDC;
+    use crate::config::{ChunkerConfig, Normalization};
+    use crate::gear::Table;
+    use crate::v2016::{Chunk, ChunkData, Error, FastCDC, MASKS, get_gear_with_seed};
+
+    #[test]
+    fn test_masks() {
+        let source = [0u8; 1024];
+        let chunker = AsyncStreamCDC::new(source.as_slice(), 64, 256, 1024);
+        assert_eq!(chunker.mask_l, MASKS[7]);
+        assert_eq!(chunker.mask_s, MASKS[9]);
+        let chunker = AsyncStreamCDC::new(source.as_slice(), 4096, 12288, 65535);
+        assert_eq!(chunker.mask_l, MASKS[13]);
+        assert_eq!(chunker.mask_s, MASKS[15]);
+        let chunker = AsyncStreamCDC::with_level(
+            source.as_slice(),
+            8192,
+            16384,
+            32768,
+            Normalization::Level3,
+        );
+        assert_eq!(chunker.mask_l, MASKS[11]);
+        assert_eq!(chunker.mask_s, MASKS[17]);
+    }
+
+    #[test]
+    fn test_with_config_seed() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let chunker = AsyncStreamCDC::with_config(&[0u8; 0][..], &config).unwrap();
+        assert_eq!(*chunker.gear, *get_gear_with_seed(666));
+        let chunker = AsyncStreamCDC::new(&[0u8; 0][..], 4096, 16384, 65535);
+        assert!(matches!(chunker.gear, Table::Builtin(_)));
+    }
+
+    // Sizes (min, avg, max) for which the async chunker must match FastCDC.
+    const SIZES: [(usize, usize, usize); 4] = [
+        (4096, 16384, 65535),
+        (8192, 32768, 131072),
+        (16384, 65536, 262144),
+        (64, 256, 1024),
+    ];
+
+    fn check_matches_fastcdc(
+        contents: &[u8],
+        size: (usize, usize, usize),
+        chunks: Vec<Result<ChunkData, Error>>,
+    ) {
+        let (min_size, avg_size, max_size) = size;
+        let expected: Vec<Chunk> = FastCDC::new(contents, min_size, avg_size, max_size).collect();
+        assert_eq!(chunks.len(), expected.len());
+        for (chunk, other) in chunks.into_iter().zip(expected) {
+            let chunk = chunk.unwrap();
+            assert_eq!(chunk.hash, other.hash);
+            assert_eq!(chunk.offset, other.offset as u64);
+            assert_eq!(chunk.length, other.length);
+            assert_eq!(
+                chunk.data,
+                &contents[other.offset..other.offset + other.length]
+            );
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_tokio_stream_matches_fastcdc() {
+        use tokio_stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        for size in SIZES {
+            let (min_size, avg_size, max_size) = size;
+            let mut chunker =
+                AsyncStreamCDC::new(contents.as_slice(), min_size, avg_size, max_size);
+            let chunks = chunker.as_tokio_stream().collect::<Vec<_>>().await;
+            check_matches_fastcdc(&contents, size, chunks);
+        }
+        let source = std::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let stream = chunker.into_tokio_stream();
+        let handle = tokio::spawn(async move { stream.collect::<Vec<_>>().await });
+        check_matches_fastcdc(&contents, SIZES[0], handle.await.unwrap());
+    }
+
+    #[cfg(feature = "futures")]
+    #[futures_test::test]
+    async fn test_futures_stream_matches_fastcdc() {
+        use futures::stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        for size in SIZES {
+            let (min_size, avg_size, max_size) = size;
+            let mut chunker =
+                AsyncStreamCDC::new(contents.as_slice(), min_size, avg_size, max_size);
+            let chunks = chunker.as_futures_stream().collect::<Vec<_>>().await;
+            check_matches_fastcdc(&contents, size, chunks);
+        }
+        let source = futures::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let chunks = chunker.into_futures_stream().collect::<Vec<_>>().await;
+        check_matches_fastcdc(&contents, SIZES[0], chunks);
+    }
+}
@@ -26,9 +26,36 @@
 //!
 //! The `StreamCDC` implementation is similar to `FastCDC` except that it will
 //! read data from a `Read` into an internal buffer of `max_size` and produce
-//! `ChunkData` values from the `Iterator`.
-use std::fmt;
-use std::io::Read;
+//! `ChunkData` values from the `Iterator`. With the `tokio` or `futures`
+//! feature enabled, the `AsyncStreamCDC` implementation does the same for an
+//! `AsyncRead` source, producing a `Stream` of `ChunkData` values.
+//!
+//! ## Altering the chunking
+//!
+//! The `with_level_and_seed()` constructors allow for changing the GEAR table
+//! values in order to alter the chunk boundaries. If the seed is non-zero, then
+//! it will be used to perform a bitwise exclusive OR on the values in the GEAR
+//! table, as with the `v2020` chunkers. The same table can be produced with
+//! `get_gear_with_seed()` and given to `cut_gear()` by callers who manage their
+//! own buffers.
+use crate::chunker::{ChunkInfo, ChunkResult, Chunker, Cut};
+use crate::config::{ChunkerConfig, ConfigError, logarithm2};
+#[cfg(feature = "std")]
+use crate::digest::Digested;
+use crate::digest::{ChunkHasher, DigestedCDC};
+use crate::gear::{GearTable, Table};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+use core::convert::Infallible;
+#[cfg(feature = "std")]
+use std::{fmt, io::Read};
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_stream_cdc;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use async_stream_cdc::*;
+
+pub use crate::config::Normalization;
 
 /// Smallest acceptable value for the minimum chunk size.
 pub const MINIMUM_MIN: usize = 64;
@@ -155,15 +182,65 @@ const GEAR: [u64; 256] = [
     0x8e3e4221d3614413, 0xef14d0d86bf1a22c, 0xe1d830d3f16c5ddb, 0xaabd2b2a451504e1
 ];
 
-// Find the next chunk cut point in the source.
-fn cut(
+///
+/// Get the GEAR table with the given seed.
+///
+/// This will copy the original GEAR table and perform a bitwise exclusive OR
+/// on the values using the given seed. If the seed is zero, no copying or
+/// computation is performed.
+///
+#[cfg(feature = "alloc")]
+pub fn get_gear_with_seed(seed: u64) -> Cow<'static, [u64]> {
+    gear_with_seed(seed).into_cow()
+}
+
+// Produce the table held by the chunkers for the given seed.
+pub(crate) fn gear_with_seed(seed: u64) -> Table {
+    if seed == 0 {
+        Table::Builtin(&GEAR)
+    } else {
+        let mut gear = GEAR;
+        for v in &mut gear {
+            *v ^= seed;
+        }
+        Table::generated(gear)
+    }
+}
+
+///
+/// Find the next chunk cut point in the source using the original GEAR table.
+///
+/// The returned 2-tuple consists of the 64-bit hash and the length of the
+/// chunk, which is the entire source if it is not longer than `min_size`.
+///
+pub fn cut(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+) -> (u64, usize) {
+    cut_gear(source, min_size, avg_size, max_size, mask_s, mask_l, &GEAR)
+}
+
+///
+/// Find the next chunk cut point in the source using the given GEAR table,
+/// such as one returned by [`get_gear_with_seed()`].
+///
+/// The GEAR table must have exactly 256 entries, otherwise this function will
+/// panic.
+///
+pub fn cut_gear(
     source: &[u8],
     min_size: usize,
     avg_size: usize,
     max_size: usize,
     mask_s: u64,
     mask_l: u64,
+    gear: &[u64],
 ) -> (u64, usize) {
+    let gear: &[u64; 256] = gear.try_into().expect("GEAR table must have 256 entries");
     let mut remaining = source.len();
     if remaining <= min_size {
         return (0, remaining);
@@ -180,7 +257,7 @@ fn cut(
     // desired size, which makes it harder to generate smaller chunks.
     let mut hash: u64 = 0;
     while index < center {
-        hash = (hash << 1).wrapping_add(GEAR[source[index] as usize]);
+        hash = (hash << 1).wrapping_add(gear[source[index] as usize]);
         if (hash & mask_s) == 0 {
             return (hash, index);
         }
@@ -191,7 +268,7 @@ fn cut(
     // desired size, which makes it easier to generate larger chunks.
     let last_pos = remaining;
     while index < last_pos {
-        hash = (hash << 1).wrapping_add(GEAR[source[index] as usize]);
+        hash = (hash << 1).wrapping_add(gear[source[index] as usize]);
         if (hash & mask_l) == 0 {
             return (hash, index);
         }
@@ -202,63 +279,11 @@ fn cut(
     (hash, index)
 }
 
-// Rounded base-2 logarithm; matches the behavior pre-4.0.0 so that mask
-// selection picks the bucket whose target chunk size is closest to `value`,
-// rather than always rounding down (which `usize::ilog2` does).
-fn logarithm2(value: usize) -> u32 {
-    (value as f64).log2().round() as u32
-}
-
-///
-/// The level for the normalized chunking used by FastCDC and StreamCDC.
-///
-/// Normalized chunking "generates chunks whose sizes are normalized to a
-/// specified region centered at the expected chunk size," as described in
-/// section 4.4 of the FastCDC 2016 paper.
-///
-/// Note that lower levels of normalization will result in a larger range of
-/// generated chunk sizes. It may be beneficial to widen the minimum/maximum
-/// chunk size values given to the `FastCDC` constructor in that case.
-///
-/// Note that higher levels of normalization may result in the final chunk of
-/// data being smaller than the minimum chunk size, which results in a hash
-/// value of zero (`0`) since no calculations are performed for sub-minimum
-/// chunks.
-///
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Normalization {
-    /// No chunk size normalization, produces a wide range of chunk sizes.
-    Level0,
-    /// Level 1 normalization, in which fewer chunks are outside of the desired range.
-    Level1,
-    /// Level 2 normalization, where most chunks are of the desired size.
-    Level2,
-    /// Level 3 normalization, nearly all chunks are the desired size.
-    Level3,
-}
-
-impl Normalization {
-    /// Convert the level to a numeric value (Level0 -> 0, Level1 -> 1, etc).
-    pub fn bits(&self) -> u32 {
-        match self {
-            Normalization::Level0 => 0,
-            Normalization::Level1 => 1,
-            Normalization::Level2 => 2,
-            Normalization::Level3 => 3,
-        }
-    }
-}
-
-impl fmt::Display for Normalization {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.bits().fmt(f)
-    }
-}
-
 ///
 /// Represents a chunk returned from the FastCDC iterator.
 ///
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chunk {
     /// The gear hash value as of the end of the chunk.
     pub hash: u64,
@@ -302,6 +327,7 @@ pub struct FastCDC<'a> {
     max_size: usize,
     mask_s: u64,
     mask_l: u64,
+    gear: Table,
 }
 
 impl<'a> FastCDC<'a> {
@@ -323,6 +349,21 @@ impl<'a> FastCDC<'a> {
         avg_size: usize,
         max_size: usize,
         level: Normalization,
+    ) -> Self {
+        FastCDC::with_level_and_seed(source, min_size, avg_size, max_size, level, 0)
+    }
+
+    ///
+    /// Create a new `FastCDC` with the given normalization level and seed to be
+    /// XOR'd with the values in the gear table.
+    ///
+    pub fn with_level_and_seed(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        seed: u64,
     ) -> Self {
         debug_assert!(min_size >= MINIMUM_MIN);
         debug_assert!(min_size <= MINIMUM_MAX);
@@ -343,9 +384,48 @@ impl<'a> FastCDC<'a> {
             max_size,
             mask_s,
             mask_l,
+            gear: gear_with_seed(seed),
+        }
+    }
+
+    ///
+    /// Create a new `FastCDC` with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    /// To record the table in a fingerprint, pass it to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_gear(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        Self {
+            gear: Table::generated(*gear.values()),
+            ..FastCDC::with_level(source, min_size, avg_size, max_size, level)
         }
     }
 
+    ///
+    /// Create a new `FastCDC` using the validated parameters of the given
+    /// `ChunkerConfig`.
+    ///
+    pub fn with_config(source: &'a [u8], config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(FastCDC::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
     ///
     /// Find the next cut point in the data, where `start` is the position from
     /// which to start processing the source data, and `remaining` are the
@@ -360,51 +440,120 @@ impl<'a> FastCDC<'a> {
     ///
     pub fn cut(&self, start: usize, remaining: usize) -> (u64, usize) {
         let end = start + remaining;
-        let (hash, count) = cut(
+        let (hash, count) = cut_gear(
             &self.source[start..end],
             self.min_size,
             self.avg_size,
             self.max_size,
             self.mask_s,
             self.mask_l,
+            &self.gear,
         );
         (hash, start + count)
     }
-}
 
-impl Iterator for FastCDC<'_> {
-    type Item = Chunk;
+    ///
+    /// Find the next chunks, writing the hash and the end offset of each one
+    /// into `points` until either it is full or the source is exhausted, and
+    /// returning the number of cut points written, as with
+    /// [`v2020::FastCDC::cut_points_into()`].
+    ///
+    /// [`v2020::FastCDC::cut_points_into()`]: crate::v2020::FastCDC::cut_points_into
+    ///
+    pub fn cut_points_into(&mut self, points: &mut [(u64, usize)]) -> usize {
+        let mut count = 0;
+        while count < points.len() {
+            let Some(point) = self.next_cut() else {
+                break;
+            };
+            points[count] = point;
+            count += 1;
+        }
+        count
+    }
 
-    fn next(&mut self) -> Option<Chunk> {
+    ///
+    /// Append the hash and the end offset of each of the remaining chunks to
+    /// `points`, as with [`cut_points_into()`](FastCDC::cut_points_into).
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn collect_cut_points(&mut self, points: &mut Vec<(u64, usize)>) {
+        while let Some(point) = self.next_cut() {
+            points.push(point);
+        }
+    }
+
+    // Find the next cut point, advancing past the chunk it ends.
+    fn next_cut(&mut self) -> Option<(u64, usize)> {
         if self.remaining == 0 {
+            return None;
+        }
+        let (hash, cutpoint) = self.cut(self.processed, self.remaining);
+        if cutpoint == 0 {
             None
         } else {
-            let (hash, cutpoint) = self.cut(self.processed, self.remaining);
-            if cutpoint == 0 {
-                None
-            } else {
-                let offset = self.processed;
-                let length = cutpoint - offset;
-                self.processed += length;
-                self.remaining -= length;
-                Some(Chunk {
-                    hash,
-                    offset,
-                    length,
-                })
-            }
+            self.remaining -= cutpoint - self.processed;
+            self.processed = cutpoint;
+            Some((hash, cutpoint))
         }
     }
 
+    ///
+    /// Produce the digest of each chunk along with the chunk, using the given
+    /// hasher, as with [`v2020::FastCDC::with_hasher()`].
+    ///
+    /// [`v2020::FastCDC::with_hasher()`]: crate::v2020::FastCDC::with_hasher
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> DigestedCDC<'a, Self, H> {
+        let source = self.source;
+        DigestedCDC::new(self, source, hasher, |chunk| {
+            chunk.offset..chunk.offset + chunk.length
+        })
+    }
+}
+
+impl Iterator for FastCDC<'_> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        let offset = self.processed;
+        self.next_cut().map(|(hash, cutpoint)| Chunk {
+            hash,
+            offset,
+            length: cutpoint - offset,
+        })
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
         let upper_bound = self.remaining / self.min_size;
         (1.min(upper_bound), Some(upper_bound))
     }
 }
 
+impl Chunker for FastCDC<'_> {
+    type Error = Infallible;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Infallible>> {
+        let source = self.source;
+        let available = self.remaining;
+        let max_size = self.max_size;
+        self.next().map(|chunk| {
+            let data = &source[chunk.offset..chunk.offset + chunk.length];
+            let info = ChunkInfo {
+                hash: chunk.hash,
+                offset: chunk.offset as u64,
+                length: chunk.length,
+                cut: Cut::of_gear(chunk.length, available, max_size),
+            };
+            Ok((info, data))
+        })
+    }
+}
+
 ///
 /// The error type returned from the `StreamCDC` iterator.
 ///
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub enum Error {
     /// End of source data reached.
@@ -415,6 +564,7 @@ pub enum Error {
     Other(String),
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -425,14 +575,17 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
         Error::IoError(error)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Error> for std::io::Error {
     fn from(error: Error) -> Self {
         match error {
@@ -446,7 +599,9 @@ impl From<Error> for std::io::Error {
 ///
 /// Represents a chunk returned from the StreamCDC iterator.
 ///
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkData {
     /// The gear hash value as of the end of the chunk.
     pub hash: u64,
@@ -455,9 +610,17 @@ pub struct ChunkData {
     /// Length of the chunk in bytes.
     pub length: usize,
     /// Source bytes contained in this chunk.
+    #[cfg_attr(feature = "serde", serde(with = "crate::bytes", default))]
     pub data: Vec<u8>,
 }
 
+#[cfg(feature = "std")]
+impl AsRef<[u8]> for ChunkData {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
 ///
 /// The FastCDC chunker implementation from 2016 with streaming support.
 ///
@@ -478,6 +641,7 @@ pub struct ChunkData {
 /// }
 /// ```
 ///
+#[cfg(feature = "std")]
 pub struct StreamCDC<R: Read> {
     /// Buffer of data from source for finding cut points.
     buffer: Vec<u8>,
@@ -491,13 +655,18 @@ pub struct StreamCDC<R: Read> {
     processed: u64,
     /// True when the source produces no more data.
     eof: bool,
+    /// Number of bytes at the front of `buffer` belonging to the chunk most
+    /// recently returned by [`Chunker::next_entry`], yet to be discarded.
+    pending: usize,
     min_size: usize,
     avg_size: usize,
     max_size: usize,
     mask_s: u64,
     mask_l: u64,
+    gear: Table,
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> StreamCDC<R> {
     ///
     /// Construct a `StreamCDC` that will process bytes from the given source.
@@ -517,6 +686,21 @@ impl<R: Read> StreamCDC<R> {
         avg_size: usize,
         max_size: usize,
         level: Normalization,
+    ) -> Self {
+        StreamCDC::with_level_and_seed(source, min_size, avg_size, max_size, level, 0)
+    }
+
+    ///
+    /// Create a new `StreamCDC` with the given normalization level and seed to
+    /// be XOR'd with the values in the gear table.
+    ///
+    pub fn with_level_and_seed(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        seed: u64,
     ) -> Self {
         debug_assert!(min_size >= MINIMUM_MIN);
         debug_assert!(min_size <= MINIMUM_MAX);
@@ -534,15 +718,76 @@ impl<R: Read> StreamCDC<R> {
             length: 0,
             source,
             eof: false,
+            pending: 0,
             processed: 0,
             min_size,
             avg_size,
             max_size,
             mask_s,
             mask_l,
+            gear: gear_with_seed(seed),
+        }
+    }
+
+    ///
+    /// Create a new `StreamCDC` with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    /// To record the table in a fingerprint, pass it to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_gear(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        Self {
+            gear: Table::generated(*gear.values()),
+            ..StreamCDC::with_level(source, min_size, avg_size, max_size, level)
         }
     }
 
+    ///
+    /// Create a new `StreamCDC` using the validated parameters of the given
+    /// `ChunkerConfig`.
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(StreamCDC::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
+    ///
+    /// Compute the digest of each chunk with the given hasher, producing a
+    /// `ChunkData` and digest pair for each chunk.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "blake3")] {
+    /// # use std::fs::File;
+    /// # use fastcdc::v2016::StreamCDC;
+    /// let source = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let chunker = StreamCDC::new(source, 4096, 16384, 65535);
+    /// for result in chunker.with_hasher(blake3::Hasher::new()) {
+    ///     let (chunk, digest) = result.unwrap();
+    ///     println!("offset={} digest={:02x?}", chunk.offset, digest);
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> Digested<Self, H> {
+        Digested::new(self, hasher)
+    }
+
     /// Fill the buffer with data from the source, returning the number of bytes
     /// read (zero if end of source has been reached).
     fn fill_buffer(&mut self) -> Result<usize, Error> {
@@ -582,38 +827,59 @@ impl<R: Read> StreamCDC<R> {
         }
     }
 
-    /// Find the next chunk in the source. If the end of the source has been
-    /// reached, returns `Error::Empty` as the error.
-    fn read_chunk(&mut self) -> Result<ChunkData, Error> {
+    /// Discards the bytes of the chunk most recently returned by
+    /// [`Chunker::next_entry`], if any.
+    fn discard_pending(&mut self) {
+        if self.pending > 0 {
+            self.buffer.copy_within(self.pending..self.length, 0);
+            self.length -= self.pending;
+            self.pending = 0;
+        }
+    }
+
+    /// Find the next cut point in the buffer, returning the hash and the
+    /// length of the chunk. If the end of the source has been reached, returns
+    /// `Error::Empty` as the error.
+    fn find_chunk(&mut self) -> Result<(u64, usize), Error> {
+        self.discard_pending();
         self.fill_buffer()?;
         if self.length == 0 {
             Err(Error::Empty)
         } else {
-            let (hash, count) = cut(
+            let (hash, count) = cut_gear(
                 &self.buffer[..self.length],
                 self.min_size,
                 self.avg_size,
                 self.max_size,
                 self.mask_s,
                 self.mask_l,
+                &self.gear,
             );
             if count == 0 {
                 Err(Error::Empty)
             } else {
-                let offset = self.processed;
-                self.processed += count as u64;
-                let data = self.drain_bytes(count)?;
-                Ok(ChunkData {
-                    hash,
-                    offset,
-                    length: count,
-                    data,
-                })
+                Ok((hash, count))
             }
         }
     }
+
+    /// Find the next chunk in the source. If the end of the source has been
+    /// reached, returns `Error::Empty` as the error.
+    fn read_chunk(&mut self) -> Result<ChunkData, Error> {
+        let (hash, count) = self.find_chunk()?;
+        let offset = self.processed;
+        self.processed += count as u64;
+        let data = self.drain_bytes(count)?;
+        Ok(ChunkData {
+            hash,
+            offset,
+            length: count,
+            data,
+        })
+    }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> Iterator for StreamCDC<R> {
     type Item = Result<ChunkData, Error>;
 
@@ -627,9 +893,34 @@ impl<R: Read> Iterator for StreamCDC<R> {
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl<R: Read> Chunker for StreamCDC<R> {
+    type Error = Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Error>> {
+        match self.find_chunk() {
+            Err(Error::Empty) => None,
+            Err(error) => Some(Err(error)),
+            Ok((hash, count)) => {
+                let offset = self.processed;
+                self.processed += count as u64;
+                self.pending = count;
+                let info = ChunkInfo {
+                    hash,
+                    offset,
+                    length: count,
+                    cut: Cut::of_gear(count, self.length, self.max_size),
+                };
+                Some(Ok((info, &self.buffer[..count])))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
+    use crate::test_util::Sip;
     use std::fs::{self, File};
 
     #[test]
@@ -903,6 +1194,165 @@ mod tests {
         assert_eq!(format!("{err}"), "no more data");
     }
 
+    #[test]
+    fn test_with_config() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(8192, 16384, 32768)
+            .normalization(Normalization::Level3)
+            .try_build()
+            .unwrap();
+        let expected: Vec<Chunk> =
+            FastCDC::with_level(&contents, 8192, 16384, 32768, Normalization::Level3).collect();
+        let chunker = FastCDC::with_config(&contents, &config).unwrap();
+        let results: Vec<Chunk> = chunker.collect();
+        assert_eq!(results, expected);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::with_config(file, &config).unwrap();
+        assert_eq!(chunker.count(), expected.len());
+    }
+
+    #[test]
+    fn test_get_gear_with_seed() {
+        assert!(matches!(get_gear_with_seed(0), Cow::Borrowed(_)));
+        let gear = get_gear_with_seed(666);
+        assert!(matches!(gear, Cow::Owned(_)));
+        for (index, value) in gear.iter().enumerate() {
+            assert_eq!(*value, GEAR[index] ^ 666);
+        }
+    }
+
+    #[test]
+    fn test_cut_matches_fastcdc() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let (mask_s, mask_l) = (chunker.mask_s, chunker.mask_l);
+        let mut start = 0;
+        for chunk in chunker {
+            let (hash, count) = cut(&contents[start..], 4096, 16384, 65535, mask_s, mask_l);
+            assert_eq!(hash, chunk.hash);
+            assert_eq!(count, chunk.length);
+            start += count;
+        }
+        assert_eq!(start, contents.len());
+    }
+
+    #[test]
+    fn test_with_level_and_seed() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let level = Normalization::Level1;
+        let unseeded: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let zero: Vec<Chunk> =
+            FastCDC::with_level_and_seed(&contents, 4096, 16384, 65535, level, 0).collect();
+        assert_eq!(zero, unseeded);
+        let chunker = FastCDC::with_level_and_seed(&contents, 4096, 16384, 65535, level, 666);
+        let (mask_s, mask_l) = (chunker.mask_s, chunker.mask_l);
+        let seeded: Vec<Chunk> = chunker.collect();
+        assert_ne!(seeded, unseeded);
+        assert_eq!(
+            seeded.iter().map(|c| c.length).sum::<usize>(),
+            contents.len()
+        );
+        // the public cut_gear() with a seeded table finds the same cut points
+        let gear = get_gear_with_seed(666);
+        let mut start = 0;
+        for chunk in seeded.iter() {
+            let source = &contents[start..];
+            let (hash, count) = cut_gear(source, 4096, 16384, 65535, mask_s, mask_l, &gear);
+            assert_eq!(hash, chunk.hash);
+            assert_eq!(count, chunk.length);
+            start += count;
+        }
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::with_level_and_seed(file, 4096, 16384, 65535, level, 666);
+        let streamed: Vec<Chunk> = chunker
+            .map(|result| {
+                let chunk = result.unwrap();
+                Chunk {
+                    hash: chunk.hash,
+                    offset: chunk.offset as usize,
+                    length: chunk.length,
+                }
+            })
+            .collect();
+        assert_eq!(streamed, seeded);
+    }
+
+    #[test]
+    fn test_with_level_and_gear() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let level = Normalization::Level1;
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let table = GearTable::md5();
+        let chunker = FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table);
+        assert_eq!(chunker.collect::<Vec<Chunk>>(), expected);
+        let table = GearTable::from_splitmix(7);
+        let chunker = FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table);
+        let (mask_s, mask_l) = (chunker.mask_s, chunker.mask_l);
+        let results: Vec<Chunk> = chunker.collect();
+        assert_ne!(results, expected);
+        let mut start = 0;
+        for chunk in results.iter() {
+            let source = &contents[start..];
+            let (_, count) = cut_gear(source, 4096, 16384, 65535, mask_s, mask_l, table.values());
+            assert_eq!(count, chunk.length);
+            start += count;
+        }
+        assert_eq!(start, contents.len());
+        let chunker =
+            StreamCDC::with_level_and_gear(contents.as_slice(), 4096, 16384, 65535, level, &table);
+        let lengths: Vec<usize> = chunker.map(|c| c.unwrap().length).collect();
+        let expected: Vec<usize> = results.iter().map(|c| c.length).collect();
+        assert_eq!(lengths, expected);
+    }
+
+    #[test]
+    fn test_cut_points_into() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<(u64, usize)> = FastCDC::new(&contents, 4096, 16384, 65535)
+            .map(|c| (c.hash, c.offset + c.length))
+            .collect();
+        for size in [1, 2, 3, 100] {
+            let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+            let mut points = vec![(0, 0); size];
+            let mut results = Vec::new();
+            loop {
+                let count = chunker.cut_points_into(&mut points);
+                if count == 0 {
+                    break;
+                }
+                results.extend_from_slice(&points[..count]);
+            }
+            assert_eq!(results, expected);
+            assert_eq!(chunker.next(), None);
+        }
+        // picking up where the iterator left off
+        let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        chunker.next();
+        let mut results = Vec::new();
+        chunker.collect_cut_points(&mut results);
+        assert_eq!(results, expected[1..]);
+        assert_eq!(chunker.cut_points_into(&mut [(0, 0); 4]), 0);
+    }
+
+    #[test]
+    fn test_with_config_seed() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_seed(&contents, 4096, 16384, 65535, Normalization::Level2, 666)
+                .collect();
+        let results: Vec<Chunk> = FastCDC::with_config(&contents, &config).unwrap().collect();
+        assert_eq!(results, expected);
+        let chunker = StreamCDC::with_config(contents.as_slice(), &config).unwrap();
+        let lengths: Vec<usize> = chunker.map(|c| c.unwrap().length).collect();
+        let expected: Vec<usize> = expected.iter().map(|c| c.length).collect();
+        assert_eq!(lengths, expected);
+    }
+
     #[test]
     fn test_stream_sekien_16k_chunks() {
         let file_result = File::open("test/fixtures/SekienAkashita.jpg");
@@ -957,4 +1407,36 @@ mod tests {
         }
         assert_eq!(index, 5);
     }
+
+    #[test]
+    fn test_with_hasher() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        let results: Vec<_> = chunker.with_hasher(Sip::default()).collect();
+        assert_eq!(results.len(), expected.len());
+        for ((chunk, digest), other) in results.iter().zip(&expected) {
+            assert_eq!(chunk, other);
+            let data = &contents[chunk.offset..chunk.offset + chunk.length];
+            assert_eq!(*digest, Sip::default().digest(data));
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_stream_with_hasher() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::new(file, 4096, 16384, 65535);
+        let mut index = 0;
+        for result in chunker.with_hasher(blake3::Hasher::new()) {
+            let (chunk, digest) = result.unwrap();
+            assert_eq!(chunk.offset as usize, expected[index].offset);
+            assert_eq!(chunk.length, expected[index].length);
+            assert_eq!(&digest, blake3::hash(&chunk.data).as_bytes());
+            index += 1;
+        }
+        assert_eq!(index, expected.len());
+    }
 }
@@ -3,49 +3,41 @@
 //
 
 use super::*;
-
-#[cfg(all(feature = "futures", not(feature = "tokio")))]
-use futures::{
-    io::{AsyncRead, AsyncReadExt},
-    stream::Stream,
-};
-
-#[cfg(all(feature = "tokio", not(feature = "futures")))]
-use tokio_stream::Stream;
-
-#[cfg(all(feature = "tokio", not(feature = "futures")))]
-use tokio::io::{AsyncRead, AsyncReadExt};
-
-#[cfg(all(feature = "tokio", not(feature = "futures")))]
-use async_stream::try_stream;
+use crate::async_source::{AsyncBuffer, AsyncSource, async_streams};
 
 ///
 /// An async-streamable version of the FastCDC chunker implementation from 2020
 /// with streaming support.
 ///
-/// Use `new` to construct an instance, and then [`as_stream`](AsyncStreamCDC::as_stream)
-/// to produce an async [Stream] of the chunks.
+/// Use `new` to construct an instance, and then
+/// [`as_tokio_stream`](AsyncStreamCDC::as_tokio_stream) or
+/// [`as_futures_stream`](AsyncStreamCDC::as_futures_stream) to produce an async
+/// `Stream` of the chunks, depending on whether the source implements the
+/// `AsyncRead` trait from `tokio` or from `futures`. Those streams borrow the
+/// chunker; to produce a `Send + 'static` stream that owns the chunker, such as
+/// for moving into a spawned task, use
+/// [`into_tokio_stream`](AsyncStreamCDC::into_tokio_stream) or
+/// [`into_futures_stream`](AsyncStreamCDC::into_futures_stream) instead.
 ///
-/// Both `futures` and `tokio`-based [AsyncRead] inputs are supported via
-/// feature flags. But, if necessary you can also use the
+/// Both `tokio` and `futures`-based `AsyncRead` inputs are supported via
+/// feature flags, and both features may be enabled at the same time. The
+/// [`as_stream`](AsyncStreamCDC::as_stream) and
+/// [`into_stream`](AsyncStreamCDC::into_stream) methods accept either kind of
+/// source, as described by [`AsyncSource`]. If necessary you can also use the
 /// [`async_compat`](https://docs.rs/async-compat/latest/async_compat/) crate to
 /// adapt your inputs as circumstances may require.
 ///
-/// Note that this struct allocates a [`Vec<u8>`] of `max_size` bytes to act as a
-/// buffer when reading from the source and finding chunk boundaries.
+/// Note that this struct allocates a [`Vec<u8>`] of `max_size` bytes to act as
+/// a buffer when reading from the source and finding chunk boundaries.
 ///
 /// ```no_run
-/// # use std::fs::File;
 /// # use fastcdc::v2020::AsyncStreamCDC;
-/// # #[cfg(all(feature = "futures", not(feature = "tokio")))]
-/// # use futures::stream::StreamExt;
-/// # #[cfg(all(feature = "tokio", not(feature = "futures")))]
-/// # use tokio_stream::StreamExt;
-///
+/// # #[cfg(feature = "tokio")]
 /// async fn run() {
+///     use tokio_stream::StreamExt;
 ///     let source = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
 ///     let mut chunker = AsyncStreamCDC::new(source.as_ref(), 4096, 16384, 65535);
-///     let stream = chunker.as_stream();
+///     let stream = chunker.as_tokio_stream();
 ///
 ///     let chunks = stream.collect::<Vec<_>>().await;
 ///
@@ -58,17 +50,7 @@ use async_stream::try_stream;
 ///
 pub struct AsyncStreamCDC<R> {
     /// Buffer of data from source for finding cut points.
-    buffer: Vec<u8>,
-    /// Maximum capacity of the buffer (always `max_size`).
-    capacity: usize,
-    /// Number of relevant bytes in the `buffer`.
-    length: usize,
-    /// Source from which data is read into `buffer`.
-    source: R,
-    /// Number of bytes read from the source so far.
-    processed: u64,
-    /// True when the source produces no more data.
-    eof: bool,
+    buffer: AsyncBuffer<R>,
     min_size: usize,
     avg_size: usize,
     max_size: usize,
@@ -76,13 +58,14 @@ pub struct AsyncStreamCDC<R> {
     mask_l: u64,
     mask_s_ls: u64,
     mask_l_ls: u64,
-    gear: Cow<'static, [u64]>,
-    gear_ls: Cow<'static, [u64]>,
+    gear: Table,
+    gear_ls: Table,
 }
 
-impl<R: AsyncRead + Unpin> AsyncStreamCDC<R> {
+impl<R> AsyncStreamCDC<R> {
     ///
-    /// Construct a [`AsyncStreamCDC`] that will process bytes from the given source.
+    /// Construct a [`AsyncStreamCDC`] that will process bytes from the given
+    /// source.
     ///
     /// Uses chunk size normalization level 1 by default.
     ///
@@ -121,18 +104,13 @@ impl<R: AsyncRead + Unpin> AsyncStreamCDC<R> {
         debug_assert!(avg_size <= AVERAGE_MAX);
         debug_assert!(max_size >= MAXIMUM_MIN);
         debug_assert!(max_size <= MAXIMUM_MAX);
-        let bits = avg_size.ilog2();
+        let bits = logarithm2(avg_size);
         let normalization = level.bits();
         let mask_s = MASKS[(bits + normalization) as usize];
         let mask_l = MASKS[(bits - normalization) as usize];
-        let (gear, gear_ls) = get_gear_with_seed(seed);
+        let (gear, gear_ls) = gear_with_seed(seed);
         Self {
-            buffer: vec![0_u8; max_size],
-            capacity: max_size,
-            length: 0,
-            source,
-            eof: false,
-            processed: 0,
+            buffer: AsyncBuffer::new(source, max_size),
             min_size,
             avg_size,
             max_size,
@@ -145,114 +123,107 @@ impl<R: AsyncRead + Unpin> AsyncStreamCDC<R> {
         }
     }
 
-    /// Fill the buffer with data from the source, returning the number of bytes
-    /// read (zero if end of source has been reached).
-    async fn fill_buffer(&mut self) -> Result<usize, Error> {
-        // this code originally copied from asuran crate
-        if self.eof {
-            Ok(0)
-        } else {
-            let mut all_bytes_read = 0;
-            while !self.eof && self.length < self.capacity {
-                let bytes_read = self.source.read(&mut self.buffer[self.length..]).await?;
-                if bytes_read == 0 {
-                    self.eof = true;
-                } else {
-                    self.length += bytes_read;
-                    all_bytes_read += bytes_read;
-                }
-            }
-            Ok(all_bytes_read)
+    ///
+    /// Create a new [`AsyncStreamCDC`] with the given normalization level and
+    /// the gear tables derived from the given secret key by
+    /// [`get_gear_with_key()`].
+    ///
+    pub fn with_level_and_key(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        key: &[u8; 16],
+    ) -> Self {
+        let (gear, gear_ls) = gear_with_key(key);
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(source, min_size, avg_size, max_size, level)
         }
     }
 
-    /// Drains a specified number of bytes from the buffer, then resizes the
-    /// buffer back to `capacity` size in preparation for further reads.
-    fn drain_bytes(&mut self, count: usize) -> Result<Vec<u8>, Error> {
-        // this code originally copied from asuran crate
-        if count > self.length {
-            Err(Error::Other(format!(
-                "drain_bytes() called with count larger than length: {} > {}",
-                count, self.length
-            )))
-        } else {
-            let mut data = Vec::with_capacity(count);
-            data.extend_from_slice(&self.buffer[..count]);
-            self.buffer.copy_within(count..self.length, 0);
-            self.length -= count;
-            Ok(data)
+    ///
+    /// Create a new [`AsyncStreamCDC`] with the given normalization level and
+    /// gear table, in place of the built-in table.
+    ///
+    pub fn with_level_and_gear(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        let (gear, gear_ls) = gear.tables();
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(source, min_size, avg_size, max_size, level)
         }
     }
 
-    /// Find the next chunk in the source. If the end of the source has been
-    /// reached, returns `Error::Empty` as the error.
-    async fn read_chunk(&mut self) -> Result<ChunkData, Error> {
-        self.fill_buffer().await?;
-        if self.length == 0 {
-            Err(Error::Empty)
-        } else {
-            let (hash, count) = cut_gear(
-                &self.buffer[..self.length],
-                self.min_size,
-                self.avg_size,
-                self.max_size,
-                self.mask_s,
-                self.mask_l,
-                self.mask_s_ls,
-                self.mask_l_ls,
-                &self.gear,
-                &self.gear_ls,
-            );
-            if count == 0 {
-                Err(Error::Empty)
-            } else {
-                let offset = self.processed;
-                self.processed += count as u64;
-                let data = self.drain_bytes(count)?;
-                Ok(ChunkData {
-                    hash,
-                    offset,
-                    length: count,
-                    data,
-                })
-            }
-        }
+    ///
+    /// Create a new [`AsyncStreamCDC`] using the validated parameters of the
+    /// given [`ChunkerConfig`].
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(Self::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
     }
 
-    #[cfg(all(feature = "tokio", not(feature = "futures")))]
-    pub fn as_stream(&mut self) -> impl Stream<Item = Result<ChunkData, Error>> + '_ {
-        try_stream! {
-            loop {
-                match self.read_chunk().await {
-                    Ok(chunk) => yield chunk,
-                    Err(Error::Empty) => {
-                        break;
-                    }
-                    error @ Err(_) => {
-                        error?;
-                    }
-                }
-            }
+    /// Find the next chunk in the source, returning `None` if the end of the
+    /// source has been reached.
+    async fn read_chunk<B>(&mut self) -> Option<Result<ChunkData, Error>>
+    where
+        R: AsyncSource<B>,
+    {
+        if let Err(error) = self.buffer.fill_buffer().await {
+            return Some(Err(error.into()));
+        }
+        if self.buffer.data().is_empty() {
+            return None;
+        }
+        let (hash, count) = cut_gear(
+            self.buffer.data(),
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.mask_s,
+            self.mask_l,
+            self.mask_s_ls,
+            self.mask_l_ls,
+            &self.gear,
+            &self.gear_ls,
+        );
+        if count == 0 {
+            None
+        } else {
+            let (offset, data) = self.buffer.drain_bytes(count);
+            Some(Ok(ChunkData {
+                hash,
+                offset,
+                length: count,
+                data,
+            }))
         }
-    }
-
-    #[cfg(all(feature = "futures", not(feature = "tokio")))]
-    pub fn as_stream(&mut self) -> impl Stream<Item = Result<ChunkData, Error>> + '_ {
-        futures::stream::unfold(self, |this| async {
-            let chunk = this.read_chunk().await;
-            if let Err(Error::Empty) = chunk {
-                None
-            } else {
-                Some((chunk, this))
-            }
-        })
     }
 }
 
+async_streams!();
+
 #[cfg(test)]
 mod tests {
     use super::AsyncStreamCDC;
-    use crate::v2020::MASKS;
+    use crate::config::{ChunkerConfig, Normalization};
+    use crate::v2020::{ChunkData, Error, MASKS};
 
     #[test]
     #[should_panic]
@@ -308,6 +279,43 @@ mod tests {
         let chunker = AsyncStreamCDC::new(source.as_slice(), 1_048_576, 4_194_304, 16_777_216);
         assert_eq!(chunker.mask_l, MASKS[21]);
         assert_eq!(chunker.mask_s, MASKS[23]);
+        // the average is rounded to the nearest power of two, not down
+        let chunker = AsyncStreamCDC::new(source.as_slice(), 4096, 12288, 65535);
+        assert_eq!(chunker.mask_l, MASKS[13]);
+        assert_eq!(chunker.mask_s, MASKS[15]);
+    }
+
+    #[test]
+    fn test_with_config() {
+        let source = [0u8; 1024];
+        let config = ChunkerConfig::builder(8192, 16384, 32768)
+            .normalization(Normalization::Level3)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let chunker = AsyncStreamCDC::with_config(source.as_slice(), &config).unwrap();
+        assert_eq!(chunker.mask_l, MASKS[11]);
+        assert_eq!(chunker.mask_s, MASKS[17]);
+        assert_eq!(chunker.gear[0], crate::v2020::GEAR[0] ^ 666);
+    }
+
+    #[test]
+    fn test_with_level_and_key() {
+        let source = [0u8; 1024];
+        let key = [7u8; 16];
+        let chunker = AsyncStreamCDC::with_level_and_key(
+            source.as_slice(),
+            8192,
+            16384,
+            32768,
+            Normalization::Level3,
+            &key,
+        );
+        assert_eq!(chunker.mask_l, MASKS[11]);
+        assert_eq!(chunker.mask_s, MASKS[17]);
+        let (gear, gear_ls) = crate::v2020::get_gear_with_key(&key);
+        assert_eq!(*chunker.gear, *gear);
+        assert_eq!(*chunker.gear_ls, *gear_ls);
     }
 
     struct ExpectedChunk {
@@ -317,19 +325,108 @@ mod tests {
         digest: String,
     }
 
-    #[cfg(all(feature = "futures", not(feature = "tokio")))]
-    use futures::stream::StreamExt;
-    #[cfg(all(feature = "tokio", not(feature = "futures")))]
-    use tokio_stream::StreamExt;
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_as_stream_tokio() {
+        use tokio_stream::StreamExt;
+        // a source that implements only the tokio trait, even if the futures
+        // feature is also enabled
+        let source = tokio::fs::File::open("test/fixtures/SekienAkashita.jpg")
+            .await
+            .unwrap();
+        let mut chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let chunks = chunker.as_stream().collect::<Vec<_>>().await;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    #[cfg(feature = "futures")]
+    #[futures_test::test]
+    async fn test_into_stream_futures() {
+        use futures::stream::StreamExt;
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+        // a source that implements only the futures trait, even if the tokio
+        // feature is also enabled
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let source = futures::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let stream = chunker.into_stream();
+        assert_send_static(&stream);
+        let chunks = stream.collect::<Vec<_>>().await;
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_tokio_stream_sekien_16k_chunks() {
+        use tokio_stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunker = AsyncStreamCDC::new(contents.as_slice(), 4096, 16384, 65535);
+        let chunks = chunker.as_tokio_stream().collect::<Vec<_>>().await;
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    #[cfg(feature = "futures")]
+    #[futures_test::test]
+    async fn test_futures_stream_sekien_16k_chunks() {
+        use futures::stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunker = AsyncStreamCDC::new(contents.as_slice(), 4096, 16384, 65535);
+        let chunks = chunker.as_futures_stream().collect::<Vec<_>>().await;
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_tokio_into_stream_spawn() {
+        use tokio_stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let source = std::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let stream = chunker.into_tokio_stream();
+        let handle = tokio::spawn(async move { stream.collect::<Vec<_>>().await });
+        let chunks = handle.await.unwrap();
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    #[cfg(feature = "futures")]
+    #[futures_test::test]
+    async fn test_futures_into_stream() {
+        use futures::stream::StreamExt;
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let source = futures::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let stream = chunker.into_futures_stream();
+        assert_send_static(&stream);
+        let chunks = stream.collect::<Vec<_>>().await;
+        check_sekien_16k_chunks(&contents, chunks);
+    }
 
-    #[cfg_attr(all(feature = "tokio", not(feature = "futures")), tokio::test)]
-    #[cfg_attr(all(feature = "futures", not(feature = "tokio")), futures_test::test)]
-    async fn test_iter_sekien_16k_chunks() {
-        let read_result = std::fs::read("test/fixtures/SekienAkashita.jpg");
-        assert!(read_result.is_ok());
-        let contents = read_result.unwrap();
+    #[cfg(all(feature = "tokio", feature = "blake3"))]
+    #[tokio::test]
+    async fn test_tokio_stream_with_hasher() {
+        use crate::digest::Digested;
+        use tokio_stream::StreamExt;
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let source = std::io::Cursor::new(contents.clone());
+        let chunker = AsyncStreamCDC::new(source, 4096, 16384, 65535);
+        let stream = Box::pin(chunker.into_tokio_stream());
+        let results = Digested::new(stream, blake3::Hasher::new())
+            .collect::<Vec<_>>()
+            .await;
+        let mut chunks = Vec::new();
+        for result in results {
+            let (chunk, digest) = result.unwrap();
+            assert_eq!(&digest, blake3::hash(&chunk.data).as_bytes());
+            chunks.push(Ok(chunk));
+        }
+        check_sekien_16k_chunks(&contents, chunks);
+    }
+
+    fn check_sekien_16k_chunks(contents: &[u8], chunks: Vec<Result<ChunkData, Error>>) {
         // The digest values are not needed here, but they serve to validate
-        // that the streaming version tested below is returning the correct
+        // that the streaming version tested above is returning the correct
         // chunk data on each iteration.
         let expected_chunks = [
             ExpectedChunk {
@@ -363,10 +460,6 @@ mod tests {
                 digest: "f6996300fce24d3da56c81ea52e5f4f461ce6adb4496f65252996e1082471aac".into(),
             },
         ];
-        let mut chunker = AsyncStreamCDC::new(contents.as_ref(), 4096, 16384, 65535);
-        let stream = chunker.as_stream();
-
-        let chunks = stream.collect::<Vec<_>>().await;
 
         let mut index = 0;
 
@@ -375,9 +468,12 @@ mod tests {
             assert_eq!(chunk.hash, expected_chunks[index].hash);
             assert_eq!(chunk.offset, expected_chunks[index].offset);
             assert_eq!(chunk.length, expected_chunks[index].length);
+            assert_eq!(
+                chunk.data,
+                &contents[(chunk.offset as usize)..(chunk.offset as usize) + chunk.length]
+            );
             let mut hasher = blake3::Hasher::new();
-            hasher
-                .update(&contents[(chunk.offset as usize)..(chunk.offset as usize) + chunk.length]);
+            hasher.update(&chunk.data);
             let digest = format!("{}", hasher.finalize()).to_lowercase();
             assert_eq!(digest, expected_chunks[index].digest);
             index += 1;
@@ -0,0 +1,333 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+
+// Number of buffers whose hash chains are interleaved in the scan loop.
+const LANES: usize = 4;
+
+///
+/// Chunk each of the given buffers with the parameters of the given
+/// [`ChunkerConfig`], returning the chunks of each buffer in the same order as
+/// the buffers, with offsets relative to the start of their buffer.
+///
+/// The result is identical to running [`FastCDC::with_config()`] on each of
+/// the buffers separately. However, as the computation of the gear hash is a
+/// serial chain of dependent instructions, scanning a single buffer leaves
+/// much of the processor idle. This function scans several buffers at once,
+/// interleaving their hash computations in the same loop, which improves the
+/// throughput of a single thread when chunking many buffers, such as the
+/// contents of many small files.
+///
+/// ```
+/// use fastcdc::config::ChunkerConfig;
+/// use fastcdc::v2020::{chunk_many, FastCDC};
+///
+/// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let config = ChunkerConfig::builder(4096, 16384, 65535).try_build().unwrap();
+/// let buffers: Vec<&[u8]> = contents.chunks(20_000).collect();
+/// let results = chunk_many(&buffers, &config);
+/// assert_eq!(results.len(), buffers.len());
+/// for (chunks, buffer) in results.iter().zip(&buffers) {
+///     let expected: Vec<_> = FastCDC::with_config(buffer, &config).unwrap().collect();
+///     assert_eq!(chunks, &expected);
+/// }
+/// ```
+///
+pub fn chunk_many(sources: &[&[u8]], config: &ChunkerConfig) -> Vec<Vec<Chunk>> {
+    let chunker = FastCDC::with_level_and_seed(
+        &[],
+        config.min_size(),
+        config.avg_size(),
+        config.max_size(),
+        config.normalization(),
+        config.seed(),
+    );
+    let mut results: Vec<Vec<Chunk>> = alloc::vec![Vec::new(); sources.len()];
+    let mut scanner = Scanner {
+        chunker: &chunker,
+        sources,
+        next_source: 0,
+    };
+    let mut lanes: [Lane; LANES] = Default::default();
+    let mut active = true;
+    for lane in lanes.iter_mut() {
+        active &= scanner.start_source(lane, &mut results);
+    }
+    // Interleave the lanes for as long as each of them has a buffer to scan.
+    while active {
+        let steps = lanes.iter().map(|l| l.limit - l.index).min().unwrap();
+        scanner.scan(&mut lanes, steps);
+        for lane in lanes.iter_mut() {
+            let cut = if let Some(cut) = lane.found.take() {
+                Some(cut)
+            } else if lane.index < lane.limit {
+                None
+            } else if lane.limit < lane.limit2 {
+                // enter the region of the large masks
+                lane.limit = lane.limit2;
+                lane.mask = chunker.mask_l;
+                lane.mask_ls = chunker.mask_l_ls;
+                None
+            } else {
+                Some((lane.hash, lane.remaining))
+            };
+            if let Some((hash, length)) = cut {
+                results[lane.source].push(Chunk {
+                    hash,
+                    offset: lane.processed,
+                    length,
+                });
+                lane.processed += length;
+                active &= scanner.start_chunk(lane, &mut results);
+            }
+        }
+    }
+    // Finish the buffers of the remaining lanes one at a time, restarting the
+    // chunk that was in progress.
+    for lane in lanes.iter().filter(|l| l.source < sources.len()) {
+        let mut remainder = chunker.clone();
+        remainder.rechunk(sources[lane.source]);
+        remainder.processed = lane.processed;
+        remainder.remaining -= lane.processed;
+        results[lane.source].extend(remainder);
+    }
+    results
+}
+
+// State of the scan of one buffer, equivalent to the local variables of the
+// scalar `cut_gear_arr()` for the chunk being scanned.
+#[derive(Default)]
+struct Lane {
+    // index of the buffer within the sources
+    source: usize,
+    // position of the current chunk within the buffer
+    processed: usize,
+    // portion of the buffer that may be part of the current chunk
+    remaining: usize,
+    // index of the next byte pair within the chunk
+    index: usize,
+    // byte pair at which the masks change, or the scan ends
+    limit: usize,
+    limit2: usize,
+    hash: u64,
+    mask: u64,
+    mask_ls: u64,
+    // cut point found by the last scan
+    found: Option<(u64, usize)>,
+}
+
+struct Scanner<'a, 'b> {
+    chunker: &'b FastCDC<'b>,
+    sources: &'b [&'a [u8]],
+    next_source: usize,
+}
+
+impl Scanner<'_, '_> {
+    // Assign the next buffer to the lane, returning false if none remain.
+    fn next_source(&mut self, lane: &mut Lane) -> bool {
+        if self.next_source == self.sources.len() {
+            lane.source = self.sources.len();
+            return false;
+        }
+        lane.source = self.next_source;
+        lane.processed = 0;
+        self.next_source += 1;
+        true
+    }
+
+    // Assign the next buffer to the lane and prepare to scan its first chunk,
+    // returning false if there are no more buffers to be scanned.
+    fn start_source(&mut self, lane: &mut Lane, results: &mut [Vec<Chunk>]) -> bool {
+        self.next_source(lane) && self.start_chunk(lane, results)
+    }
+
+    // Prepare the lane for scanning the next chunk of its buffer, moving on to
+    // the next buffer once the current one is done. Chunks that do not need to
+    // be scanned are added to the results right away, for as many buffers as
+    // necessary. Returns false if there are no more buffers to be scanned.
+    fn start_chunk(&mut self, lane: &mut Lane, results: &mut [Vec<Chunk>]) -> bool {
+        let chunker = self.chunker;
+        let mut remaining = self.sources[lane.source].len() - lane.processed;
+        while remaining <= chunker.min_size {
+            if remaining > 0 {
+                results[lane.source].push(Chunk {
+                    hash: 0,
+                    offset: lane.processed,
+                    length: remaining,
+                });
+            }
+            if !self.next_source(lane) {
+                return false;
+            }
+            remaining = self.sources[lane.source].len();
+        }
+        let mut center = chunker.avg_size;
+        if remaining > chunker.max_size {
+            remaining = chunker.max_size;
+        } else if remaining < center {
+            center = remaining;
+        }
+        lane.remaining = remaining;
+        lane.index = chunker.min_size / 2;
+        lane.limit = (center / 2).max(lane.index);
+        lane.limit2 = (remaining / 2).max(lane.limit);
+        lane.hash = 0;
+        lane.mask = chunker.mask_s;
+        lane.mask_ls = chunker.mask_s_ls;
+        true
+    }
+
+    // Advance every lane by the given number of byte pairs, or until a cut
+    // point is found in any of them.
+    fn scan(&self, lanes: &mut [Lane; LANES], steps: usize) {
+        let gear: &[u64; 256] = self.chunker.gear[..].try_into().unwrap();
+        let gear_ls: &[u64; 256] = self.chunker.gear_ls[..].try_into().unwrap();
+        // the byte pairs to be scanned by each lane
+        let pairs: [&[[u8; 2]]; LANES] = core::array::from_fn(|i| {
+            let lane = &lanes[i];
+            let start = lane.processed + lane.index * 2;
+            let (pairs, _) = self.sources[lane.source][start..start + steps * 2].as_chunks();
+            pairs
+        });
+        let mut hash: [u64; LANES] = core::array::from_fn(|i| lanes[i].hash);
+        let mask: [u64; LANES] = core::array::from_fn(|i| lanes[i].mask);
+        let mask_ls: [u64; LANES] = core::array::from_fn(|i| lanes[i].mask_ls);
+        // the hash and position within the pair of the cut point of each lane
+        let mut found = [None; LANES];
+        let mut step = 0;
+        while step < steps {
+            for i in 0..LANES {
+                let [first, second] = pairs[i][step];
+                let mut h = (hash[i] << 2).wrapping_add(gear_ls[first as usize]);
+                if (h & mask_ls[i]) == 0 {
+                    found[i] = Some((h, 0));
+                } else {
+                    h = h.wrapping_add(gear[second as usize]);
+                    if (h & mask[i]) == 0 {
+                        found[i] = Some((h, 1));
+                    }
+                }
+                hash[i] = h;
+            }
+            step += 1;
+            if found.iter().any(Option::is_some) {
+                break;
+            }
+        }
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            lane.found = found[i].map(|(h, extra)| (h, (lane.index + step - 1) * 2 + extra));
+            lane.hash = hash[i];
+            lane.index += step;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn expected(sources: &[&[u8]], config: &ChunkerConfig) -> Vec<Vec<Chunk>> {
+        sources
+            .iter()
+            .map(|source| FastCDC::with_config(source, config).unwrap().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_many_empty() {
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        assert!(chunk_many(&[], &config).is_empty());
+        let results = chunk_many(&[&[], &[1, 2, 3]], &config);
+        assert_eq!(results[0], []);
+        assert_eq!(
+            results[1],
+            [Chunk {
+                hash: 0,
+                offset: 0,
+                length: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_chunk_many_tiny_buffers() {
+        // many consecutive buffers that need no scanning, between larger ones
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        let mut sources: Vec<&[u8]> = vec![&contents[..]];
+        for i in 0..50_000 {
+            sources.push(if i % 3 == 0 {
+                &[]
+            } else {
+                &contents[i % 1000..][..100]
+            });
+        }
+        sources.push(&contents[..]);
+        let results = chunk_many(&sources, &config);
+        assert_eq!(results, expected(&sources, &config));
+        assert_eq!(results[1], []);
+        assert_eq!(results[2].len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_many_sekien() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        // buffers of varying sizes, including some shorter than the minimum
+        let mut sources: Vec<&[u8]> = Vec::new();
+        let mut start = 0;
+        for size in [contents.len(), 1000, 50_000, 4096, 4097, 0, 70_000, 33_333] {
+            let end = (start + size).min(contents.len());
+            sources.push(&contents[start..end]);
+            start = if end == contents.len() { 0 } else { end };
+        }
+        for _ in 0..3 {
+            sources.push(&contents);
+        }
+        let results = chunk_many(&sources, &config);
+        assert_eq!(results, expected(&sources, &config));
+        assert_eq!(results[0].len(), 5);
+        assert_eq!(results[0][0].hash, 17968276318003433923);
+    }
+
+    #[test]
+    fn test_chunk_many_configs() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let sources: Vec<&[u8]> = contents
+            .chunks(7919)
+            .chain(contents.chunks(30011))
+            .collect();
+        for (min, avg, max) in [(64, 256, 1024), (2048, 8192, 32768), (8192, 16384, 32768)] {
+            for level in [Normalization::Level0, Normalization::Level3] {
+                for seed in [0, 666] {
+                    let config = ChunkerConfig::builder(min, avg, max)
+                        .normalization(level)
+                        .seed(seed)
+                        .try_build()
+                        .unwrap();
+                    assert_eq!(chunk_many(&sources, &config), expected(&sources, &config));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunk_many_zeros() {
+        // the scan of every chunk runs to the maximum size
+        let zeros = vec![0u8; 200_000];
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .try_build()
+            .unwrap();
+        let sources: Vec<&[u8]> = vec![&zeros, &zeros[..150_000], &zeros, &zeros[..70_000], &zeros];
+        assert_eq!(chunk_many(&sources, &config), expected(&sources, &config));
+    }
+}
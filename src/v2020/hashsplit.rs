@@ -0,0 +1,360 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+use crate::digest::ChunkHasher;
+use core::ops::Range;
+
+/// Version of the encoding of the index nodes of a [`HashSplitTree`].
+pub const HASHSPLIT_VERSION: u8 = 1;
+
+// Leading byte of the input to the hash of an index node.
+const INDEX_TAG: u8 = 0x03;
+
+///
+/// Applies content-defined chunking to a sequence of digests, such as the
+/// digests of the chunks of a file, grouping consecutive digests into index
+/// nodes, and recursively to the digests of those nodes, producing a
+/// [`HashSplitTree`].
+///
+/// The digests are fed to the gear hash of the [`FastCDC`] chunker, one byte
+/// at a time, and a group ends after a digest at which the masked hash is
+/// zero, with the sizes of the groups, counted in digests, normalized as with
+/// the sizes of the chunks. As the hash only depends on the last 64 bytes, an
+/// insertion or removal of digests changes only the groups around it, and the
+/// rest of the index nodes are unchanged, at every level of the tree.
+///
+/// ```
+/// # #[cfg(feature = "blake3")] {
+/// use fastcdc::v2020::{FastCDC, HashSplitter};
+/// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+/// let digests: Vec<_> = chunker
+///     .with_hasher(blake3::Hasher::new())
+///     .map(|(_, digest)| digest)
+///     .collect();
+/// let splitter = HashSplitter::new(2, 4, 16);
+/// let tree = splitter.build(&mut blake3::Hasher::new(), &digests);
+/// for node in tree.nodes() {
+///     println!("{:02x?} -> entries {:?}", node.digest, node.children);
+/// }
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashSplitter {
+    min_count: usize,
+    avg_count: usize,
+    max_count: usize,
+    mask_s: u64,
+    mask_l: u64,
+    gear: Table,
+}
+
+impl HashSplitter {
+    ///
+    /// Construct a [`HashSplitter`] producing index nodes of the given minimum,
+    /// average, and maximum number of entries.
+    ///
+    /// Uses normalization level 1 by default.
+    ///
+    /// # Panics
+    ///
+    /// If `min_count` is less than 2, which could prevent the tree from
+    /// shrinking towards a root, or the counts are not in increasing order.
+    ///
+    pub fn new(min_count: usize, avg_count: usize, max_count: usize) -> Self {
+        HashSplitter::with_level(min_count, avg_count, max_count, Normalization::Level1)
+    }
+
+    ///
+    /// Create a new [`HashSplitter`] with the given normalization level.
+    ///
+    pub fn with_level(
+        min_count: usize,
+        avg_count: usize,
+        max_count: usize,
+        level: Normalization,
+    ) -> Self {
+        HashSplitter::with_level_and_seed(min_count, avg_count, max_count, level, 0)
+    }
+
+    ///
+    /// Create a new [`HashSplitter`] with the given normalization level and
+    /// seed to be XOR'd with the values in the gear table.
+    ///
+    pub fn with_level_and_seed(
+        min_count: usize,
+        avg_count: usize,
+        max_count: usize,
+        level: Normalization,
+        seed: u64,
+    ) -> Self {
+        assert!(min_count >= 2, "index nodes must have at least 2 entries");
+        assert!(min_count <= avg_count && avg_count <= max_count);
+        let bits = logarithm2(avg_count);
+        let normalization = level.bits();
+        let (gear, _) = gear_with_seed(seed);
+        Self {
+            min_count,
+            avg_count,
+            max_count,
+            mask_s: high_bits(bits + normalization),
+            mask_l: high_bits(bits.saturating_sub(normalization)),
+            gear,
+        }
+    }
+
+    ///
+    /// Find the groups of the given digests, returning the index of the end of
+    /// each group, the last being the number of digests.
+    ///
+    /// A group ends after the digest at which the gear hash, masked with the
+    /// small mask before the average count and the large mask after it, is
+    /// zero, but not before the minimum count, and always at the maximum.
+    ///
+    pub fn boundaries<D: AsRef<[u8]>>(&self, digests: &[D]) -> Vec<usize> {
+        let mut ends = Vec::new();
+        let mut hash: u64 = 0;
+        let mut count = 0;
+        for (index, digest) in digests.iter().enumerate() {
+            for byte in digest.as_ref() {
+                hash = (hash << 1).wrapping_add(self.gear[*byte as usize]);
+            }
+            count += 1;
+            let mask = if count < self.avg_count {
+                self.mask_s
+            } else {
+                self.mask_l
+            };
+            if (count >= self.min_count && hash & mask == 0) || count == self.max_count {
+                ends.push(index + 1);
+                count = 0;
+            }
+        }
+        if count > 0 {
+            ends.push(digests.len());
+        }
+        ends
+    }
+
+    ///
+    /// Build the tree of index nodes over the given digests, in order, using
+    /// the hasher that produced them to compute the digest of each node.
+    ///
+    pub fn build<H, D>(&self, hasher: &mut H, digests: &[D]) -> HashSplitTree<D>
+    where
+        H: ChunkHasher<Digest = D>,
+        D: AsRef<[u8]> + Clone,
+    {
+        let mut levels: Vec<Vec<IndexNode<D>>> = Vec::new();
+        let mut entries: Vec<D> = digests.to_vec();
+        loop {
+            let mut start = 0;
+            let mut nodes: Vec<IndexNode<D>> = Vec::new();
+            for end in self.boundaries(&entries) {
+                nodes.push(IndexNode {
+                    digest: index_hash(hasher, &entries[start..end]),
+                    children: start..end,
+                });
+                start = end;
+            }
+            if nodes.is_empty() {
+                // an empty sequence has a root with no entries
+                nodes.push(IndexNode {
+                    digest: index_hash(hasher, &entries),
+                    children: 0..0,
+                });
+            }
+            entries = nodes.iter().map(|node| node.digest.clone()).collect();
+            levels.push(nodes);
+            if entries.len() == 1 {
+                break;
+            }
+        }
+        HashSplitTree {
+            leaf_count: digests.len(),
+            levels,
+        }
+    }
+}
+
+///
+/// An index node of a [`HashSplitTree`], listing a range of consecutive
+/// entries of the level below.
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexNode<D> {
+    /// Digest of the node, computed over the digests of its entries.
+    pub digest: D,
+    /// Range of the entries within the level below.
+    pub children: Range<usize>,
+}
+
+///
+/// Tree of index nodes produced by [`HashSplitter::build()`].
+///
+/// Level 0 holds the nodes whose entries are the digests given to the
+/// splitter, and the last level holds only the root. The digest of each node
+/// is the hash of a tag byte, [`HASHSPLIT_VERSION`], and the digests of its
+/// entries, in order.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashSplitTree<D> {
+    leaf_count: usize,
+    levels: Vec<Vec<IndexNode<D>>>,
+}
+
+impl<D> HashSplitTree<D> {
+    /// The root node, whose digest identifies the whole sequence.
+    pub fn root(&self) -> &IndexNode<D> {
+        &self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The number of levels of index nodes, which is at least 1.
+    pub fn height(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The index nodes of the given level, starting from 0 for the nodes
+    /// whose entries are the original digests.
+    pub fn level(&self, level: usize) -> &[IndexNode<D>] {
+        &self.levels[level]
+    }
+
+    /// The number of digests over which the tree was built.
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Iterate over all of the index nodes, from the lowest level to the root.
+    pub fn nodes(&self) -> impl Iterator<Item = &IndexNode<D>> {
+        self.levels.iter().flatten()
+    }
+}
+
+// Mask selecting the given number of the high bits of the hash, which depend
+// on the most bytes of the input.
+fn high_bits(bits: u32) -> u64 {
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX << (64 - bits.min(64))
+    }
+}
+
+fn index_hash<H: ChunkHasher>(hasher: &mut H, entries: &[H::Digest]) -> H::Digest
+where
+    H::Digest: AsRef<[u8]>,
+{
+    hasher.update(&[INDEX_TAG, HASHSPLIT_VERSION]);
+    for entry in entries {
+        hasher.update(entry.as_ref());
+    }
+    hasher.finalize_reset()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::test_util::Sip;
+    use std::collections::HashSet;
+
+    fn digests(range: Range<u32>) -> Vec<[u8; 16]> {
+        range
+            .map(|i| Sip::default().digest(&i.to_le_bytes()))
+            .collect()
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hashsplit_min_count() {
+        HashSplitter::new(1, 4, 16);
+    }
+
+    #[test]
+    fn test_hashsplit_boundaries() {
+        let splitter = HashSplitter::new(4, 16, 64);
+        assert!(splitter.boundaries::<[u8; 16]>(&[]).is_empty());
+        let digests = digests(0..10_000);
+        let ends = splitter.boundaries(&digests);
+        assert_eq!(ends.last(), Some(&digests.len()));
+        let mut start = 0;
+        for end in &ends[..ends.len() - 1] {
+            assert!(end - start >= 4 && end - start <= 64);
+            start = *end;
+        }
+        let average = digests.len() / ends.len();
+        assert!((12..=20).contains(&average), "average {average}");
+        // constant digests are cut at the maximum
+        let same = vec![[0u8; 16]; 200];
+        assert_eq!(splitter.boundaries(&same), vec![64, 128, 192, 200]);
+        // a different seed finds different groups
+        let seeded = HashSplitter::with_level_and_seed(4, 16, 64, Normalization::Level1, 7);
+        assert_ne!(seeded.boundaries(&digests), ends);
+    }
+
+    #[test]
+    fn test_hashsplit_tree() {
+        let splitter = HashSplitter::new(4, 16, 64);
+        let mut hasher = Sip::default();
+        let digests = digests(0..10_000);
+        let tree = splitter.build(&mut hasher, &digests);
+        assert_eq!(tree.leaf_count(), digests.len());
+        assert_eq!(tree.height(), 3);
+        assert_eq!(tree.root().children, 0..tree.level(tree.height() - 2).len());
+        let mut entries: Vec<[u8; 16]> = digests.clone();
+        for level in 0..tree.height() {
+            let nodes = tree.level(level);
+            assert_eq!(nodes.first().unwrap().children.start, 0);
+            assert_eq!(nodes.last().unwrap().children.end, entries.len());
+            for (node, next) in nodes.iter().zip(&nodes[1..]) {
+                assert_eq!(node.children.end, next.children.start);
+            }
+            for node in nodes {
+                let mut input = vec![INDEX_TAG, HASHSPLIT_VERSION];
+                for entry in &entries[node.children.clone()] {
+                    input.extend_from_slice(entry);
+                }
+                assert_eq!(node.digest, Sip::default().digest(&input));
+            }
+            entries = nodes.iter().map(|node| node.digest).collect();
+        }
+        assert_eq!(
+            tree.nodes().count(),
+            tree.levels.iter().map(Vec::len).sum::<usize>()
+        );
+        // small sequences still have a root of their own
+        let empty = splitter.build(&mut hasher, &[]);
+        assert_eq!(empty.height(), 1);
+        assert_eq!(empty.root().children, 0..0);
+        assert_eq!(
+            empty.root().digest,
+            Sip::default().digest(&[INDEX_TAG, HASHSPLIT_VERSION])
+        );
+        let single = splitter.build(&mut hasher, &digests[..1]);
+        assert_eq!(single.height(), 1);
+        assert_ne!(single.root().digest, digests[0]);
+    }
+
+    #[test]
+    fn test_hashsplit_insertion() {
+        let splitter = HashSplitter::new(4, 16, 64);
+        let mut hasher = Sip::default();
+        let original = digests(0..10_000);
+        let mut modified = original.clone();
+        modified.splice(5_000..5_000, digests(20_000..20_003));
+        let before = splitter.build(&mut hasher, &original);
+        let after = splitter.build(&mut hasher, &modified);
+        assert_ne!(before.root().digest, after.root().digest);
+        // only the nodes around the insertion change, at each level
+        for level in 0..before.height().min(after.height()) {
+            let old: HashSet<_> = before.level(level).iter().map(|n| n.digest).collect();
+            let new: HashSet<_> = after.level(level).iter().map(|n| n.digest).collect();
+            let changed = new.difference(&old).count();
+            assert!(changed <= 3, "level {level} changed {changed}");
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+
+///
+/// A chunk of data lent by [`LendingStreamCDC`], borrowing the bytes from the
+/// internal buffer of the chunker.
+///
+/// The borrow ends when the next chunk is requested, so copy the data (e.g.
+/// with `to_vec()`) if it needs to outlive the call to `next_chunk()`.
+///
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ChunkRef<'a> {
+    /// The gear hash value as of the end of the chunk.
+    pub hash: u64,
+    /// Starting byte position within the source.
+    pub offset: u64,
+    /// The bytes of the chunk.
+    pub data: &'a [u8],
+}
+
+impl ChunkRef<'_> {
+    /// Length of the chunk in bytes.
+    pub fn length(&self) -> usize {
+        self.data.len()
+    }
+}
+
+///
+/// The FastCDC chunker implementation from 2020 with streaming support that
+/// lends each chunk from its internal buffer rather than allocating a new
+/// vector for every chunk.
+///
+/// Use `new` to construct an instance, and then call [`next_chunk()`] until it
+/// returns `None`. Each [`ChunkRef`] borrows the chunker, hence this type does
+/// not implement `Iterator`.
+///
+/// The buffer holds twice the maximum chunk size. Chunks are found and lent in
+/// place, and the unprocessed data is moved to the front of the buffer only
+/// when there is no longer room for a maximum sized chunk after it, which
+/// happens at most once for every `max_size` bytes of input. By comparison,
+/// [`StreamCDC`] allocates and copies every chunk, and shifts the rest of its
+/// buffer after each one.
+///
+/// [`next_chunk()`]: LendingStreamCDC::next_chunk
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use fastcdc::v2020::LendingStreamCDC;
+/// let source = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let mut chunker = LendingStreamCDC::new(source, 4096, 16384, 65535);
+/// while let Some(result) = chunker.next_chunk() {
+///     let chunk = result.unwrap();
+///     println!("offset={} length={}", chunk.offset, chunk.length());
+/// }
+/// ```
+///
+pub struct LendingStreamCDC<R: Read> {
+    /// Buffer of data from source for finding cut points.
+    buffer: Vec<u8>,
+    /// Position in `buffer` of the first byte not yet returned in a chunk.
+    start: usize,
+    /// Position in `buffer` just past the last byte read from the source.
+    end: usize,
+    /// Source from which data is read into `buffer`.
+    source: R,
+    /// Number of bytes returned in chunks so far.
+    processed: u64,
+    /// True when the source produces no more data.
+    eof: bool,
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+    mask_s_ls: u64,
+    mask_l_ls: u64,
+    gear: Table,
+    gear_ls: Table,
+}
+
+impl<R: Read> LendingStreamCDC<R> {
+    ///
+    /// Construct a [`LendingStreamCDC`] that will process bytes from the given
+    /// source.
+    ///
+    /// Uses chunk size normalization level 1 by default.
+    ///
+    pub fn new(source: R, min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::with_level(source, min_size, avg_size, max_size, Normalization::Level1)
+    }
+
+    ///
+    /// Create a new [`LendingStreamCDC`] with the given normalization level.
+    ///
+    pub fn with_level(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+    ) -> Self {
+        Self::with_level_and_seed(source, min_size, avg_size, max_size, level, 0)
+    }
+
+    ///
+    /// Create a new [`LendingStreamCDC`] with the given normalization level and
+    /// seed to be XOR'd with the values in the gear tables.
+    ///
+    pub fn with_level_and_seed(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        seed: u64,
+    ) -> Self {
+        debug_assert!(min_size >= MINIMUM_MIN);
+        debug_assert!(min_size <= MINIMUM_MAX);
+        debug_assert!(avg_size >= AVERAGE_MIN);
+        debug_assert!(avg_size <= AVERAGE_MAX);
+        debug_assert!(max_size >= MAXIMUM_MIN);
+        debug_assert!(max_size <= MAXIMUM_MAX);
+        let bits = logarithm2(avg_size);
+        let normalization = level.bits();
+        let mask_s = MASKS[(bits + normalization) as usize];
+        let mask_l = MASKS[(bits - normalization) as usize];
+        let (gear, gear_ls) = gear_with_seed(seed);
+        Self {
+            buffer: vec![0_u8; max_size * 2],
+            start: 0,
+            end: 0,
+            source,
+            processed: 0,
+            eof: false,
+            min_size,
+            avg_size,
+            max_size,
+            mask_s,
+            mask_l,
+            mask_s_ls: mask_s << 1,
+            mask_l_ls: mask_l << 1,
+            gear,
+            gear_ls,
+        }
+    }
+
+    ///
+    /// Create a new [`LendingStreamCDC`] with the given normalization level and
+    /// the gear tables derived from the given secret key by
+    /// [`get_gear_with_key()`].
+    ///
+    pub fn with_level_and_key(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        key: &[u8; 16],
+    ) -> Self {
+        let (gear, gear_ls) = gear_with_key(key);
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`LendingStreamCDC`] with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    pub fn with_level_and_gear(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        let (gear, gear_ls) = gear.tables();
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`LendingStreamCDC`] using the validated parameters of the
+    /// given [`ChunkerConfig`].
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(Self::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
+    /// Read from the source until the buffer holds at least `max_size` bytes
+    /// beyond `start`, or the source is exhausted.
+    fn fill_buffer(&mut self) -> Result<(), Error> {
+        if self.eof || self.end - self.start >= self.max_size {
+            return Ok(());
+        }
+        if self.buffer.len() - self.start < self.max_size {
+            // not enough room for a maximum sized chunk, shift the remaining
+            // data to the front of the buffer
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        while !self.eof && self.end - self.start < self.max_size {
+            let bytes_read = self.source.read(&mut self.buffer[self.end..])?;
+            if bytes_read == 0 {
+                self.eof = true;
+            } else {
+                self.end += bytes_read;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the next cut point in the buffer, returning the hash and the
+    /// length of the chunk, or `None` if the source has been exhausted.
+    fn find_chunk(&mut self) -> Result<Option<(u64, usize)>, Error> {
+        self.fill_buffer()?;
+        if self.start == self.end {
+            return Ok(None);
+        }
+        let (hash, count) = cut_gear(
+            &self.buffer[self.start..self.end],
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.mask_s,
+            self.mask_l,
+            self.mask_s_ls,
+            self.mask_l_ls,
+            &self.gear,
+            &self.gear_ls,
+        );
+        Ok(Some((hash, count)))
+    }
+
+    ///
+    /// Find the next chunk in the source, returning `None` once the source has
+    /// been exhausted.
+    ///
+    /// The returned chunk borrows the internal buffer of the chunker, and the
+    /// bytes are released when this method is called again.
+    ///
+    pub fn next_chunk(&mut self) -> Option<Result<ChunkRef<'_>, Error>> {
+        match self.find_chunk() {
+            Err(error) => Some(Err(error)),
+            Ok(None) => None,
+            Ok(Some((hash, count))) => {
+                let offset = self.processed;
+                let data = &self.buffer[self.start..self.start + count];
+                self.processed += count as u64;
+                self.start += count;
+                Some(Ok(ChunkRef { hash, offset, data }))
+            }
+        }
+    }
+}
+
+impl<R: Read> Chunker for LendingStreamCDC<R> {
+    type Error = Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Error>> {
+        match self.find_chunk() {
+            Err(error) => Some(Err(error)),
+            Ok(None) => None,
+            Ok(Some((hash, count))) => {
+                let info = ChunkInfo {
+                    hash,
+                    offset: self.processed,
+                    length: count,
+                    cut: Cut::of_gear(count, self.end - self.start, self.max_size),
+                };
+                let data = &self.buffer[self.start..self.start + count];
+                self.processed += count as u64;
+                self.start += count;
+                Some(Ok((info, data)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Trickle;
+    use std::fs::{self, File};
+
+    fn collect<R: Read>(mut chunker: LendingStreamCDC<R>, contents: &[u8]) -> Vec<Chunk> {
+        let mut results = Vec::new();
+        while let Some(result) = chunker.next_chunk() {
+            let chunk = result.unwrap();
+            let start = chunk.offset as usize;
+            assert_eq!(chunk.data, &contents[start..start + chunk.length()]);
+            results.push(Chunk {
+                hash: chunk.hash,
+                offset: start,
+                length: chunk.length(),
+            });
+        }
+        results
+    }
+
+    #[test]
+    fn test_lending_sekien_16k_chunks() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = LendingStreamCDC::new(file, 4096, 16384, 65535);
+        let results = collect(chunker, &contents);
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        assert_eq!(results.len(), 5);
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_lending_small_reads() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = LendingStreamCDC::new(Trickle(&contents), 1024, 2048, 4096);
+        let results = collect(chunker, &contents);
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 1024, 2048, 4096).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_lending_with_key() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let key = [7u8; 16];
+        let chunker = LendingStreamCDC::with_level_and_key(
+            Trickle(&contents),
+            4096,
+            16384,
+            65535,
+            Normalization::Level1,
+            &key,
+        );
+        let results = collect(chunker, &contents);
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_key(&contents, 4096, 16384, 65535, Normalization::Level1, &key)
+                .collect();
+        assert_eq!(results, expected);
+        let plain: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        assert_ne!(results, plain);
+    }
+
+    #[test]
+    fn test_lending_with_level_and_gear() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let level = Normalization::Level2;
+        let table = GearTable::from_splitmix(42);
+        let chunker = LendingStreamCDC::with_level_and_gear(
+            Trickle(&contents),
+            4096,
+            16384,
+            65535,
+            level,
+            &table,
+        );
+        let results = collect(chunker, &contents);
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table).collect();
+        assert_eq!(results, expected);
+        let plain: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        assert_ne!(results, plain);
+    }
+
+    #[test]
+    fn test_lending_all_zeros() {
+        // every chunk is the maximum size, forcing the buffer to be compacted
+        let contents = vec![0u8; 10_000];
+        let chunker = LendingStreamCDC::new(contents.as_slice(), 64, 256, 1024);
+        let results = collect(chunker, &contents);
+        assert_eq!(results.len(), 10);
+        for (index, chunk) in results.iter().enumerate() {
+            assert_eq!(chunk.offset, index * 1024);
+        }
+        assert_eq!(results[9].length, 784);
+    }
+
+    #[test]
+    fn test_lending_empty() {
+        let mut chunker = LendingStreamCDC::new([].as_slice(), 64, 256, 1024);
+        assert!(chunker.next_chunk().is_none());
+        assert!(chunker.next_chunk().is_none());
+    }
+
+    #[test]
+    fn test_lending_chunker() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let mut chunker = LendingStreamCDC::with_config(contents.as_slice(), &config).unwrap();
+        let mut expected = FastCDC::with_config(&contents, &config).unwrap();
+        while let Some(result) = chunker.next_entry() {
+            let (info, data) = result.unwrap();
+            let (other, other_data) = expected.next_entry().unwrap().unwrap();
+            assert_eq!(info, other);
+            assert_eq!(data, other_data);
+        }
+        assert!(expected.next_entry().is_none());
+    }
+}
@@ -33,6 +33,15 @@
 //! will read data from a [`Read`] into an internal buffer of `max_size` and
 //! produce [`ChunkData`] values from the [`Iterator`].
 //!
+//! The [`LendingStreamCDC`] implementation also reads from a [`Read`], but
+//! lends each chunk as a [`ChunkRef`] that borrows its internal buffer,
+//! avoiding the allocation and copying of every chunk performed by
+//! [`StreamCDC`].
+//!
+//! When the data arrives in blocks from a source that does not implement
+//! [`Read`], such as a network callback, [`PushCDC`] accepts each block as it
+//! arrives and reports the chunks that have been completed so far.
+//!
 //! ## Altering the chunking
 //!
 //! The [`FastCDC::with_level_and_seed`] and [`StreamCDC::with_level_and_seed`]
@@ -42,15 +51,53 @@
 //! tables. Modifying the GEAR hash is useful for preventing attacks based on
 //! monitoring the chunking behavior and using that information to infer other
 //! attributes of the data that would otherwise be unknown.
-use std::borrow::Cow;
-use std::fmt;
-use std::io::Read;
+//!
+//! However, a seed alters every value in the table in the same way, so the
+//! differences between the values are unchanged, and an attacker who can
+//! observe the chunk sizes may still learn much about the table. For stronger
+//! protection, the `with_level_and_key()` constructors derive the tables from
+//! a 128-bit secret key using a pseudorandom function, as described for
+//! [`get_gear_with_key()`].
+use crate::chunker::{ChunkInfo, ChunkResult, Chunker, Cut};
+use crate::config::{ChunkerConfig, ConfigError, logarithm2};
+#[cfg(feature = "std")]
+use crate::digest::Digested;
+use crate::digest::{ChunkHasher, DigestedCDC};
+use crate::gear::{GearTable, Table};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+use core::convert::Infallible;
+#[cfg(feature = "std")]
+use std::{fmt, io::Read};
+
+#[cfg(feature = "alloc")]
+mod chunk_many;
+#[cfg(feature = "alloc")]
+mod hashsplit;
+#[cfg(feature = "std")]
+mod lending_stream_cdc;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "std")]
+mod push_cdc;
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+mod simd;
+#[cfg(feature = "alloc")]
+pub use chunk_many::*;
+#[cfg(feature = "alloc")]
+pub use hashsplit::*;
+#[cfg(feature = "std")]
+pub use lending_stream_cdc::*;
+#[cfg(feature = "std")]
+pub use push_cdc::*;
 
 #[cfg(any(feature = "tokio", feature = "futures"))]
 mod async_stream_cdc;
 #[cfg(any(feature = "tokio", feature = "futures"))]
 pub use async_stream_cdc::*;
 
+pub use crate::config::Normalization;
+
 /// Smallest acceptable value for the minimum chunk size.
 pub const MINIMUM_MIN: usize = 64;
 /// Largest acceptable value for the minimum chunk size.
@@ -109,7 +156,7 @@ pub const MASKS: [u64; 26] = [
 // The program to produce this table is named table64.rs in examples.
 //
 #[rustfmt::skip]
-const GEAR: [u64; 256] = [
+pub(crate) const GEAR: [u64; 256] = [
     0x3b5d3c7d207e37dc, 0x784d68ba91123086, 0xcd52880f882e7298, 0xeacf8e4e19fdcca7,
     0xc31f385dfbd1632b, 0x1d5f27001e25abe6, 0x83130bde3c9ad991, 0xc4b225676e9b7649,
     0xaa329b29e08eb499, 0xb67fcbd21e577d58, 0x0027baaada2acf6b, 0xe3ef2d5ac73c2226,
@@ -257,10 +304,17 @@ const GEAR_LS: [u64; 256] = [
 /// peform a bitwise exclusive OR on the values using the given seed. If the
 /// seed is zero, no copying or computation is performed.
 ///
+#[cfg(feature = "alloc")]
 pub fn get_gear_with_seed(seed: u64) -> (Cow<'static, [u64]>, Cow<'static, [u64]>) {
+    let (gear, gear_ls) = gear_with_seed(seed);
+    (gear.into_cow(), gear_ls.into_cow())
+}
+
+// Produce the tables held by the chunkers for the given seed.
+pub(crate) fn gear_with_seed(seed: u64) -> (Table, Table) {
     if seed == 0 {
-        // No allocation: borrow the 'static tables directly.
-        (Cow::Borrowed(&GEAR), Cow::Borrowed(&GEAR_LS))
+        // No copying: borrow the 'static tables directly.
+        (Table::Builtin(&GEAR), Table::Builtin(&GEAR_LS))
     } else {
         let mut gear = GEAR; // copy onto the stack (2 KB)
         let mut gear_ls = GEAR_LS; // copy onto the stack (2 KB)
@@ -271,11 +325,41 @@ pub fn get_gear_with_seed(seed: u64) -> (Cow<'static, [u64]>, Cow<'static, [u64]
         for v in &mut gear_ls {
             *v ^= seed_ls;
         }
-        // Move the modified stack copies into heap-owned Vecs.
-        (Cow::Owned(gear.into()), Cow::Owned(gear_ls.into()))
+        (Table::generated(gear), Table::generated(gear_ls))
     }
 }
 
+///
+/// Derive a GEAR table, and its left-shifted twin, from the given secret key.
+///
+/// Each entry is the SipHash-2-4 of a domain separation string followed by the
+/// index of the entry, keyed with `key`. Unlike the tables produced by
+/// [`get_gear_with_seed()`], in which every entry is altered by the same value
+/// and hence the differences between the entries are preserved, the entries of
+/// the derived table are independent of the original table and of each other.
+/// Without the key, an attacker cannot compute the chunk boundaries of chosen
+/// data offline.
+///
+/// The derivation is stable: the same key always produces the same tables.
+///
+#[cfg(feature = "alloc")]
+pub fn get_gear_with_key(key: &[u8; 16]) -> (Cow<'static, [u64]>, Cow<'static, [u64]>) {
+    let (gear, gear_ls) = gear_with_key(key);
+    (gear.into_cow(), gear_ls.into_cow())
+}
+
+// Produce the tables held by the chunkers for the given key.
+pub(crate) fn gear_with_key(key: &[u8; 16]) -> (Table, Table) {
+    let mut message = *b"fastcdc-gear-v1\0";
+    let mut gear = [0_u64; 256];
+    for (index, v) in gear.iter_mut().enumerate() {
+        message[15] = index as u8;
+        *v = crate::siphash::siphash24(key, &message);
+    }
+    let gear_ls = gear.map(|v| v << 1);
+    (Table::generated(gear), Table::generated(gear_ls))
+}
+
 ///
 /// Find the next chunk cut point in the source using the original GEAR tables.
 ///
@@ -306,6 +390,12 @@ pub fn cut(
 /// the per-byte table lookups are in-bounds (no `panic_bounds_check` in the
 /// hot scan loop).
 ///
+/// On x86_64 processors that support AVX-512, the source is scanned using
+/// vector instructions when the average size is at least 16 KiB, and on those
+/// that support only AVX2, when it is at least 64 KiB. Either produces exactly
+/// the same cut points and hash values as the scalar loop, which is faster for
+/// smaller average sizes (see the `cut_gear` group of the benchmarks).
+///
 #[allow(clippy::too_many_arguments)]
 pub fn cut_gear(
     source: &[u8],
@@ -318,6 +408,39 @@ pub fn cut_gear(
     mask_l_ls: u64,
     gear: &[u64],
     gear_ls: &[u64],
+) -> (u64, usize) {
+    let gear: &[u64; 256] = gear.try_into().expect("GEAR table must have 256 entries");
+    let gear_ls: &[u64; 256] = gear_ls
+        .try_into()
+        .expect("GEAR_LS table must have 256 entries");
+    #[cfg(all(feature = "std", target_arch = "x86_64"))]
+    if let Some(result) = simd::cut_gear(
+        source, min_size, avg_size, max_size, mask_s, mask_l, mask_s_ls, mask_l_ls, gear, gear_ls,
+    ) {
+        return result;
+    }
+    cut_gear_arr(
+        source, min_size, avg_size, max_size, mask_s, mask_l, mask_s_ls, mask_l_ls, gear, gear_ls,
+    )
+}
+
+///
+/// Find the next chunk cut point as with [`cut_gear()`], always using the
+/// scalar loop, such as to compare its throughput with that of the vector
+/// instructions.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn cut_gear_scalar(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+    mask_s_ls: u64,
+    mask_l_ls: u64,
+    gear: &[u64],
+    gear_ls: &[u64],
 ) -> (u64, usize) {
     let gear: &[u64; 256] = gear.try_into().expect("GEAR table must have 256 entries");
     let gear_ls: &[u64; 256] = gear_ls
@@ -408,62 +531,11 @@ fn cut_gear_arr(
     (hash, remaining)
 }
 
-// Rounded base-2 logarithm; matches the behavior pre-4.0.0 so that mask
-// selection picks the bucket whose target chunk size is closest to `value`,
-// rather than always rounding down (which `usize::ilog2` does).
-fn logarithm2(value: usize) -> u32 {
-    (value as f64).log2().round() as u32
-}
-
-///
-/// The level for the normalized chunking used by FastCDC.
-///
-/// Normalized chunking "generates chunks whose sizes are normalized to a
-/// specified region centered at the expected chunk size," as described in
-/// section 4.4 of the FastCDC 2016 paper.
-///
-/// Note that lower levels of normalization will result in a larger range of
-/// generated chunk sizes. It may be beneficial to widen the minimum/maximum
-/// chunk size values given to the [`FastCDC`] constructor in that case.
-///
-/// Note that higher levels of normalization may result in the final chunk of
-/// data being smaller than the minimum chunk size, which results in a hash
-/// value of zero since no calculations are performed for sub-minimum chunks.
-///
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Normalization {
-    /// No chunk size normalization, produces a wide range of chunk sizes.
-    Level0,
-    /// Level 1 normalization, in which fewer chunks are outside of the desired range.
-    Level1,
-    /// Level 2 normalization, where most chunks are of the desired size.
-    Level2,
-    /// Level 3 normalization, nearly all chunks are the desired size.
-    Level3,
-}
-
-impl Normalization {
-    /// Convert the level to a numeric value (Level0 -> 0, Level1 -> 1, etc).
-    pub fn bits(&self) -> u32 {
-        match self {
-            Normalization::Level0 => 0,
-            Normalization::Level1 => 1,
-            Normalization::Level2 => 2,
-            Normalization::Level3 => 3,
-        }
-    }
-}
-
-impl fmt::Display for Normalization {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.bits().fmt(f)
-    }
-}
-
 ///
 /// Represents a chunk returned from the [`FastCDC`] iterator.
 ///
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chunk {
     /// The gear hash value as of the end of the chunk.
     pub hash: u64,
@@ -509,8 +581,8 @@ pub struct FastCDC<'a> {
     mask_l: u64,
     mask_s_ls: u64,
     mask_l_ls: u64,
-    gear: Cow<'static, [u64]>,
-    gear_ls: Cow<'static, [u64]>,
+    gear: Table,
+    gear_ls: Table,
 }
 
 impl<'a> FastCDC<'a> {
@@ -558,7 +630,7 @@ impl<'a> FastCDC<'a> {
         let normalization = level.bits();
         let mask_s = MASKS[(bits + normalization) as usize];
         let mask_l = MASKS[(bits - normalization) as usize];
-        let (gear, gear_ls) = get_gear_with_seed(seed);
+        let (gear, gear_ls) = gear_with_seed(seed);
         Self {
             source,
             processed: 0,
@@ -575,6 +647,71 @@ impl<'a> FastCDC<'a> {
         }
     }
 
+    ///
+    /// Create a new [`FastCDC`] with the given normalization level and the gear
+    /// tables derived from the given secret key by [`get_gear_with_key()`].
+    ///
+    /// To record the table in a fingerprint, pass [`GearTable::from_key()`] to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_key(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        key: &[u8; 16],
+    ) -> Self {
+        let (gear, gear_ls) = gear_with_key(key);
+        Self {
+            gear,
+            gear_ls,
+            ..FastCDC::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`FastCDC`] with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    /// To record the table in a fingerprint, pass it to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_gear(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        let (gear, gear_ls) = gear.tables();
+        Self {
+            gear,
+            gear_ls,
+            ..FastCDC::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`FastCDC`] using the validated parameters of the given
+    /// [`ChunkerConfig`].
+    ///
+    pub fn with_config(source: &'a [u8], config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(FastCDC::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
     ///
     /// Find the next cut point in the data, where `start` is the position from
     /// which to start processing the source data, and `remaining` are the
@@ -630,41 +767,148 @@ impl<'a> FastCDC<'a> {
         self.remaining = source.len();
         self
     }
-}
 
-impl Iterator for FastCDC<'_> {
-    type Item = Chunk;
+    ///
+    /// Find the next chunks, writing the hash and the end offset of each one
+    /// into `points` until either it is full or the source is exhausted, and
+    /// returning the number of cut points written.
+    ///
+    /// The chunker is advanced as if the chunks were taken from the iterator,
+    /// hence this can be called repeatedly with the same buffer, until it
+    /// returns zero. Nothing is allocated, which makes this suitable for
+    /// finding only the boundaries of the chunks, such as for an index.
+    ///
+    /// ```
+    /// # use fastcdc::v2020::FastCDC;
+    /// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let mut chunker = FastCDC::new(&contents, 8192, 16384, 32768);
+    /// let mut points = [(0, 0); 4];
+    /// let mut ends = Vec::new();
+    /// loop {
+    ///     let count = chunker.cut_points_into(&mut points);
+    ///     if count == 0 {
+    ///         break;
+    ///     }
+    ///     ends.extend(points[..count].iter().map(|(_, end)| *end));
+    /// }
+    /// assert_eq!(ends.last(), Some(&contents.len()));
+    /// ```
+    ///
+    pub fn cut_points_into(&mut self, points: &mut [(u64, usize)]) -> usize {
+        let mut count = 0;
+        while count < points.len() {
+            let Some(point) = self.next_cut() else {
+                break;
+            };
+            points[count] = point;
+            count += 1;
+        }
+        count
+    }
 
-    fn next(&mut self) -> Option<Chunk> {
+    ///
+    /// Append the hash and the end offset of each of the remaining chunks to
+    /// `points`, as with [`cut_points_into()`](FastCDC::cut_points_into).
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn collect_cut_points(&mut self, points: &mut Vec<(u64, usize)>) {
+        while let Some(point) = self.next_cut() {
+            points.push(point);
+        }
+    }
+
+    // Find the next cut point, advancing past the chunk it ends.
+    fn next_cut(&mut self) -> Option<(u64, usize)> {
         if self.remaining == 0 {
+            return None;
+        }
+        let (hash, cutpoint) = self.cut(self.processed, self.remaining);
+        if cutpoint == 0 {
             None
         } else {
-            let (hash, cutpoint) = self.cut(self.processed, self.remaining);
-            if cutpoint == 0 {
-                None
-            } else {
-                let offset = self.processed;
-                let length = cutpoint - offset;
-                self.processed += length;
-                self.remaining -= length;
-                Some(Chunk {
-                    hash,
-                    offset,
-                    length,
-                })
-            }
+            self.remaining -= cutpoint - self.processed;
+            self.processed = cutpoint;
+            Some((hash, cutpoint))
         }
     }
 
+    ///
+    /// Produce the digest of each chunk along with the chunk, using the given
+    /// hasher.
+    ///
+    /// Each chunk is hashed from the source as soon as its cut point is found,
+    /// in a second pass over its bytes, while they are likely still in the
+    /// processor's cache (see the [`digest`](crate::digest) module).
+    ///
+    /// ```
+    /// # #[cfg(feature = "blake3")] {
+    /// use fastcdc::v2020::FastCDC;
+    /// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+    /// for (chunk, digest) in chunker.with_hasher(blake3::Hasher::new()) {
+    ///     let end = chunk.offset + chunk.length;
+    ///     assert_eq!(&digest, blake3::hash(&contents[chunk.offset..end]).as_bytes());
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> DigestedCDC<'a, Self, H> {
+        let source = self.source;
+        DigestedCDC::new(self, source, hasher, |chunk| {
+            chunk.offset..chunk.offset + chunk.length
+        })
+    }
+}
+
+impl Iterator for FastCDC<'_> {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Chunk> {
+        let offset = self.processed;
+        self.next_cut().map(|(hash, cutpoint)| Chunk {
+            hash,
+            offset,
+            length: cutpoint - offset,
+        })
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
         let upper_bound = self.remaining / self.min_size;
         (1.min(upper_bound), Some(upper_bound))
     }
 }
 
+impl Chunker for FastCDC<'_> {
+    type Error = Infallible;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Infallible>> {
+        let source = self.source;
+        let available = self.remaining;
+        let max_size = self.max_size;
+        self.next().map(|chunk| {
+            let data = &source[chunk.offset..chunk.offset + chunk.length];
+            let info = ChunkInfo {
+                hash: chunk.hash,
+                offset: chunk.offset as u64,
+                length: chunk.length,
+                cut: Cut::of_gear(chunk.length, available, max_size),
+            };
+            Ok((info, data))
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<[u8]> for ChunkData {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
 ///
 /// The error type returned from the [`StreamCDC`] iterator.
 ///
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub enum Error {
     /// End of source data reached.
@@ -675,6 +919,7 @@ pub enum Error {
     Other(String),
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -685,14 +930,17 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
         Error::IoError(error)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Error> for std::io::Error {
     fn from(error: Error) -> Self {
         match error {
@@ -706,7 +954,9 @@ impl From<Error> for std::io::Error {
 ///
 /// Represents a chunk returned from the [`StreamCDC`] iterator.
 ///
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkData {
     /// The gear hash value as of the end of the chunk.
     pub hash: u64,
@@ -715,6 +965,7 @@ pub struct ChunkData {
     /// Length of the chunk in bytes.
     pub length: usize,
     /// Source bytes contained in this chunk.
+    #[cfg_attr(feature = "serde", serde(with = "crate::bytes", default))]
     pub data: Vec<u8>,
 }
 
@@ -738,6 +989,7 @@ pub struct ChunkData {
 /// }
 /// ```
 ///
+#[cfg(feature = "std")]
 pub struct StreamCDC<R: Read> {
     /// Buffer of data from source for finding cut points.
     buffer: Vec<u8>,
@@ -751,6 +1003,9 @@ pub struct StreamCDC<R: Read> {
     processed: u64,
     /// True when the source produces no more data.
     eof: bool,
+    /// Number of bytes at the front of `buffer` belonging to the chunk most
+    /// recently returned by [`Chunker::next_entry`], yet to be discarded.
+    pending: usize,
     min_size: usize,
     avg_size: usize,
     max_size: usize,
@@ -758,10 +1013,11 @@ pub struct StreamCDC<R: Read> {
     mask_l: u64,
     mask_s_ls: u64,
     mask_l_ls: u64,
-    gear: Cow<'static, [u64]>,
-    gear_ls: Cow<'static, [u64]>,
+    gear: Table,
+    gear_ls: Table,
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> StreamCDC<R> {
     ///
     /// Construct a [`StreamCDC`] that will process bytes from the given source.
@@ -806,13 +1062,14 @@ impl<R: Read> StreamCDC<R> {
         let normalization = level.bits();
         let mask_s = MASKS[(bits + normalization) as usize];
         let mask_l = MASKS[(bits - normalization) as usize];
-        let (gear, gear_ls) = get_gear_with_seed(seed);
+        let (gear, gear_ls) = gear_with_seed(seed);
         Self {
             buffer: vec![0_u8; max_size],
             capacity: max_size,
             length: 0,
             source,
             eof: false,
+            pending: 0,
             processed: 0,
             min_size,
             avg_size,
@@ -826,6 +1083,97 @@ impl<R: Read> StreamCDC<R> {
         }
     }
 
+    ///
+    /// Create a new [`StreamCDC`] with the given normalization level and the
+    /// gear tables derived from the given secret key by
+    /// [`get_gear_with_key()`].
+    ///
+    /// To record the table in a fingerprint, pass [`GearTable::from_key()`] to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_key(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        key: &[u8; 16],
+    ) -> Self {
+        let (gear, gear_ls) = gear_with_key(key);
+        Self {
+            gear,
+            gear_ls,
+            ..StreamCDC::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`StreamCDC`] with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    /// To record the table in a fingerprint, pass it to
+    /// [`Fingerprint::with_gear()`].
+    ///
+    /// [`Fingerprint::with_gear()`]: crate::fingerprint::Fingerprint::with_gear
+    ///
+    pub fn with_level_and_gear(
+        source: R,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        let (gear, gear_ls) = gear.tables();
+        Self {
+            gear,
+            gear_ls,
+            ..StreamCDC::with_level(source, min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`StreamCDC`] using the validated parameters of the given
+    /// [`ChunkerConfig`].
+    ///
+    pub fn with_config(source: R, config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(StreamCDC::with_level_and_seed(
+            source,
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
+    ///
+    /// Compute the digest of each chunk with the given hasher, producing a
+    /// [`ChunkData`] and digest pair for each chunk.
+    ///
+    /// The bytes of each chunk are hashed as soon as they are copied out of
+    /// the buffer, while they are still in the processor's cache. The chunks
+    /// produced by [`Chunker::next_entry`] are not hashed.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "blake3")] {
+    /// # use std::fs::File;
+    /// # use fastcdc::v2020::StreamCDC;
+    /// let source = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let chunker = StreamCDC::new(source, 4096, 16384, 65535);
+    /// for result in chunker.with_hasher(blake3::Hasher::new()) {
+    ///     let (chunk, digest) = result.unwrap();
+    ///     println!("offset={} digest={:02x?}", chunk.offset, digest);
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn with_hasher<H: ChunkHasher>(self, hasher: H) -> Digested<Self, H> {
+        Digested::new(self, hasher)
+    }
+
     /// Fill the buffer with data from the source, returning the number of bytes
     /// read (zero if end of source has been reached).
     fn fill_buffer(&mut self) -> Result<usize, Error> {
@@ -865,9 +1213,21 @@ impl<R: Read> StreamCDC<R> {
         }
     }
 
-    /// Find the next chunk in the source. If the end of the source has been
-    /// reached, returns `Error::Empty` as the error.
-    fn read_chunk(&mut self) -> Result<ChunkData, Error> {
+    /// Discards the bytes of the chunk most recently returned by
+    /// [`Chunker::next_entry`], if any.
+    fn discard_pending(&mut self) {
+        if self.pending > 0 {
+            self.buffer.copy_within(self.pending..self.length, 0);
+            self.length -= self.pending;
+            self.pending = 0;
+        }
+    }
+
+    /// Find the next cut point in the buffer, returning the hash and the
+    /// length of the chunk. If the end of the source has been reached, returns
+    /// `Error::Empty` as the error.
+    fn find_chunk(&mut self) -> Result<(u64, usize), Error> {
+        self.discard_pending();
         self.fill_buffer()?;
         if self.length == 0 {
             Err(Error::Empty)
@@ -887,20 +1247,28 @@ impl<R: Read> StreamCDC<R> {
             if count == 0 {
                 Err(Error::Empty)
             } else {
-                let offset = self.processed;
-                self.processed += count as u64;
-                let data = self.drain_bytes(count)?;
-                Ok(ChunkData {
-                    hash,
-                    offset,
-                    length: count,
-                    data,
-                })
+                Ok((hash, count))
             }
         }
     }
+
+    /// Find the next chunk in the source. If the end of the source has been
+    /// reached, returns `Error::Empty` as the error.
+    fn read_chunk(&mut self) -> Result<ChunkData, Error> {
+        let (hash, count) = self.find_chunk()?;
+        let offset = self.processed;
+        self.processed += count as u64;
+        let data = self.drain_bytes(count)?;
+        Ok(ChunkData {
+            hash,
+            offset,
+            length: count,
+            data,
+        })
+    }
 }
 
+#[cfg(feature = "std")]
 impl<R: Read> Iterator for StreamCDC<R> {
     type Item = Result<ChunkData, Error>;
 
@@ -914,7 +1282,31 @@ impl<R: Read> Iterator for StreamCDC<R> {
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl<R: Read> Chunker for StreamCDC<R> {
+    type Error = Error;
+
+    fn next_entry(&mut self) -> Option<ChunkResult<'_, Error>> {
+        match self.find_chunk() {
+            Err(Error::Empty) => None,
+            Err(error) => Some(Err(error)),
+            Ok((hash, count)) => {
+                let offset = self.processed;
+                self.processed += count as u64;
+                self.pending = count;
+                let info = ChunkInfo {
+                    hash,
+                    offset,
+                    length: count,
+                    cut: Cut::of_gear(count, self.length, self.max_size),
+                };
+                Some(Ok((info, &self.buffer[..count])))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::fs::{self, File};
@@ -1083,6 +1475,26 @@ mod tests {
         assert_eq!(remaining, 0);
     }
 
+    #[test]
+    fn test_cut_gear_scalar() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mask_s = MASKS[17];
+        let mask_l = MASKS[15];
+        let mut cursor = 0;
+        while cursor < contents.len() {
+            let source = &contents[cursor..];
+            let args = (mask_s, mask_l, mask_s << 1, mask_l << 1);
+            let expected = cut_gear(
+                source, 16384, 65536, 262144, args.0, args.1, args.2, args.3, &GEAR, &GEAR_LS,
+            );
+            let actual = cut_gear_scalar(
+                source, 16384, 65536, 262144, args.0, args.1, args.2, args.3, &GEAR, &GEAR_LS,
+            );
+            assert_eq!(actual, expected);
+            cursor += actual.1;
+        }
+    }
+
     #[test]
     fn test_logarithm2() {
         // Powers of two: rounded and floored log2 agree.
@@ -1250,6 +1662,134 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_with_config_matches_with_level_and_seed() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(4096, 16384, 65535)
+            .normalization(Normalization::Level2)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_seed(&contents, 4096, 16384, 65535, Normalization::Level2, 666)
+                .collect();
+        let chunker = FastCDC::with_config(&contents, &config).unwrap();
+        let results: Vec<Chunk> = chunker.collect();
+        assert_eq!(results, expected);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::with_config(file, &config).unwrap();
+        let streamed: Vec<Chunk> = chunker
+            .map(|r| {
+                let c = r.unwrap();
+                Chunk {
+                    hash: c.hash,
+                    offset: c.offset as usize,
+                    length: c.length,
+                }
+            })
+            .collect();
+        assert_eq!(streamed, expected);
+    }
+
+    #[test]
+    fn test_get_gear_with_key() {
+        // If this test fails, the cut points of every keyed chunker have changed.
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let (gear, gear_ls) = get_gear_with_key(&key);
+        assert_eq!(gear[0], 0xaa35bc4853f14d71);
+        assert_eq!(gear[1], 0xd67404595cb89c1f);
+        assert_eq!(gear[255], 0x0d397f89c426619b);
+        for (value, shifted) in gear.iter().zip(gear_ls.iter()) {
+            assert_eq!(*shifted, value << 1);
+        }
+        let (gear, _) = get_gear_with_key(&[0u8; 16]);
+        assert_eq!(gear[0], 0x3be54cabd5002ef5);
+        assert_eq!(gear[255], 0x2a01098ef329f551);
+        // the entries are not simply the original values altered by the key
+        let differences: Vec<u64> = gear.iter().zip(GEAR.iter()).map(|(a, b)| a ^ b).collect();
+        assert!(differences.iter().any(|d| *d != differences[0]));
+    }
+
+    #[test]
+    fn test_with_level_and_key() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let level = Normalization::Level1;
+        let chunker = FastCDC::with_level_and_key(&contents, 4096, 16384, 65535, level, &key);
+        let results: Vec<Chunk> = chunker.collect();
+        let lengths: Vec<usize> = results.iter().map(|c| c.length).collect();
+        assert_eq!(lengths, [9169, 20055, 21494, 33977, 20514, 4257]);
+        assert_eq!(results[0].hash, 6647637269238799505);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::with_level_and_key(file, 4096, 16384, 65535, level, &key);
+        let streamed: Vec<Chunk> = chunker
+            .map(|r| {
+                let c = r.unwrap();
+                Chunk {
+                    hash: c.hash,
+                    offset: c.offset as usize,
+                    length: c.length,
+                }
+            })
+            .collect();
+        assert_eq!(streamed, results);
+        // a different key produces different cut points
+        let other = [0u8; 16];
+        let chunker = FastCDC::with_level_and_key(&contents, 4096, 16384, 65535, level, &other);
+        assert_ne!(chunker.collect::<Vec<Chunk>>(), results);
+    }
+
+    #[test]
+    fn test_with_level_and_gear() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let level = Normalization::Level1;
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        let table = GearTable::default();
+        let chunker = FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table);
+        assert_eq!(chunker.collect::<Vec<Chunk>>(), expected);
+        // a keyed table is the same whether given as a key or as a table
+        let key = [9u8; 16];
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_key(&contents, 4096, 16384, 65535, level, &key).collect();
+        let table = GearTable::from_key(&key);
+        let chunker = FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table);
+        assert_eq!(chunker.collect::<Vec<Chunk>>(), expected);
+        let chunker =
+            StreamCDC::with_level_and_gear(contents.as_slice(), 4096, 16384, 65535, level, &table);
+        let lengths: Vec<usize> = chunker.map(|c| c.unwrap().length).collect();
+        let expected: Vec<usize> = expected.iter().map(|c| c.length).collect();
+        assert_eq!(lengths, expected);
+    }
+
+    #[test]
+    fn test_cut_points_into() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<(u64, usize)> = FastCDC::new(&contents, 4096, 16384, 65535)
+            .map(|c| (c.hash, c.offset + c.length))
+            .collect();
+        for size in [1, 2, 3, 100] {
+            let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+            let mut points = vec![(0, 0); size];
+            let mut results = Vec::new();
+            loop {
+                let count = chunker.cut_points_into(&mut points);
+                if count == 0 {
+                    break;
+                }
+                results.extend_from_slice(&points[..count]);
+            }
+            assert_eq!(results, expected);
+            assert_eq!(chunker.next(), None);
+        }
+        // picking up where the iterator left off
+        let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        chunker.next();
+        let mut results = Vec::new();
+        chunker.collect_cut_points(&mut results);
+        assert_eq!(results, expected[1..]);
+        assert_eq!(chunker.cut_points_into(&mut [(0, 0); 4]), 0);
+    }
+
     #[test]
     fn test_fastcdc_covers_every_byte() {
         // The iterator must emit contiguous chunks that cover the whole source
@@ -1328,6 +1868,72 @@ mod tests {
         assert_eq!(index, 5);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_chunks() {
+        let chunk = Chunk {
+            hash: 17968276318003433923,
+            offset: 0,
+            length: 21325,
+        };
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert_eq!(
+            json,
+            r#"{"hash":17968276318003433923,"offset":0,"length":21325}"#
+        );
+        assert_eq!(serde_json::from_str::<Chunk>(&json).unwrap(), chunk);
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunk = StreamCDC::new(file, 4096, 16384, 65535)
+            .next()
+            .unwrap()
+            .unwrap();
+        // the data is encoded as a byte string
+        let cbor = serde_cbor::to_vec(&chunk).unwrap();
+        assert!(cbor.len() < chunk.length + 64);
+        assert_eq!(serde_cbor::from_slice::<ChunkData>(&cbor).unwrap(), chunk);
+        // empty data is still encoded, as formats that are not self-describing
+        // rely on every field being present
+        chunk.data.clear();
+        let bytes = postcard::to_stdvec(&chunk).unwrap();
+        assert_eq!(postcard::from_bytes::<ChunkData>(&bytes).unwrap(), chunk);
+        let pair = (chunk.clone(), 7u8);
+        let bytes = postcard::to_stdvec(&pair).unwrap();
+        assert_eq!(postcard::from_bytes::<(ChunkData, u8)>(&bytes).unwrap(), pair);
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert_eq!(
+            json,
+            r#"{"hash":17968276318003433923,"offset":0,"length":21325,"data":[]}"#
+        );
+        // the data may be omitted by formats that are self-describing
+        let json = r#"{"hash":17968276318003433923,"offset":0,"length":21325}"#;
+        assert_eq!(serde_json::from_str::<ChunkData>(json).unwrap(), chunk);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_stream_with_hasher() {
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let chunker = StreamCDC::new(file, 4096, 16384, 65535);
+        let mut index = 0;
+        for result in chunker.with_hasher(blake3::Hasher::new()) {
+            let (chunk, digest) = result.unwrap();
+            assert_eq!(&digest, blake3::hash(&chunk.data).as_bytes());
+            index += 1;
+        }
+        assert_eq!(index, 5);
+        // the chunks are the same as without a hasher
+        let file = File::open("test/fixtures/SekienAkashita.jpg").unwrap();
+        let mut chunker = StreamCDC::new(file, 4096, 16384, 65535);
+        let first = chunker.next().unwrap().unwrap();
+        let mut chunker = chunker.with_hasher(blake3::Hasher::new());
+        let (second, digest) = chunker.next().unwrap().unwrap();
+        assert_eq!(second.offset, first.length as u64);
+        assert_eq!(second.hash, 8197189939299398838);
+        assert_eq!(&digest, blake3::hash(&second.data).as_bytes());
+        let (mut chunker, _) = chunker.into_inner();
+        assert!(chunker.next().is_some());
+    }
+
     #[test]
     fn test_stream_sekien_16k_chunks_seed_666() {
         let file_result = File::open("test/fixtures/SekienAkashita.jpg");
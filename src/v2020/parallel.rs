@@ -0,0 +1,243 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+use rayon::prelude::*;
+
+// Number of bytes scanned for candidate cut points by each task.
+const SEGMENT: usize = 1 << 20;
+
+// Number of bytes on which the gear hash depends.
+const WINDOW: usize = 64;
+
+impl FastCDC<'_> {
+    ///
+    /// Find all of the remaining chunks using every thread of the rayon thread
+    /// pool, returning the same chunks, in the same order, as collecting the
+    /// iterator would.
+    ///
+    /// The gear hash at any position depends only on the 64 bytes that precede
+    /// it, hence the source can be divided into segments that are scanned for
+    /// candidate cut points in parallel, without knowing where the chunks
+    /// begin. The chunks are then found in order by checking the candidates
+    /// against the masks in effect at each position, which depend on the start
+    /// of the chunk, which takes little time compared to the scan.
+    ///
+    /// Unlike the chunker, the scan cannot skip the minimum size of each chunk,
+    /// and each thread runs at roughly half the speed of the chunker on its
+    /// own, hence this pays off with three or more threads. Sources of up to
+    /// 1 MiB, or a pool with a single thread, are chunked sequentially.
+    ///
+    /// ```
+    /// use fastcdc::v2020::FastCDC;
+    /// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+    /// let chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+    /// let expected: Vec<_> = chunker.clone().collect();
+    /// assert_eq!(chunker.par_collect(), expected);
+    /// ```
+    ///
+    pub fn par_collect(self) -> Vec<Chunk> {
+        if self.remaining <= SEGMENT || rayon::current_num_threads() == 1 {
+            self.collect()
+        } else {
+            self.par_collect_with(SEGMENT)
+        }
+    }
+
+    fn par_collect_with(self, segment: usize) -> Vec<Chunk> {
+        let base = self.processed;
+        let data = &self.source[base..base + self.remaining];
+        // a position can only be a cut point if the hash has none of the bits
+        // that are common to all of the masks, considering that the masks for
+        // the first byte of each pair are shifted left by one
+        let filter = self.mask_s & self.mask_l & (u64::MAX >> 1);
+        let round = segment * rayon::current_num_threads();
+        let mut chunks = Vec::new();
+        let mut candidates: Vec<(usize, u64)> = Vec::new();
+        let mut scanned = 0;
+        let mut start = 0;
+        while start < data.len() {
+            let needed = (start + self.max_size).min(data.len());
+            if scanned < needed {
+                let passed = candidates.partition_point(|c| c.0 < start);
+                candidates.drain(..passed);
+                let end = (scanned + round).max(needed).min(data.len());
+                let segments: Vec<usize> = (scanned..end).step_by(segment).collect();
+                let found: Vec<Vec<(usize, u64)>> = segments
+                    .into_par_iter()
+                    .map(|from| self.candidates(data, from, (from + segment).min(end), filter))
+                    .collect();
+                candidates.extend(found.into_iter().flatten());
+                scanned = end;
+            }
+            let (hash, length) = self.stitch(data, start, &candidates);
+            chunks.push(Chunk {
+                hash,
+                offset: base + start,
+                length,
+            });
+            start += length;
+        }
+        chunks
+    }
+
+    // Find the positions within the given range at which the gear hash of the
+    // preceding 64 bytes has none of the bits of the filter.
+    fn candidates(&self, data: &[u8], from: usize, to: usize, filter: u64) -> Vec<(usize, u64)> {
+        let gear: &[u64; 256] = self.gear[..].try_into().unwrap();
+        let mut hash: u64 = 0;
+        for byte in &data[from.saturating_sub(WINDOW - 1)..from] {
+            hash = (hash << 1).wrapping_add(gear[*byte as usize]);
+        }
+        let mut found = Vec::new();
+        // advance by pairs of bytes, so that the hash computation takes half as
+        // many dependent steps, as with the chunkers
+        let (pairs, rest) = data[from..to].as_chunks::<2>();
+        for (position, [first, second]) in (from..).step_by(2).zip(pairs) {
+            let lone = (hash << 1).wrapping_add(gear[*first as usize]);
+            let both = (gear[*first as usize] << 1).wrapping_add(gear[*second as usize]);
+            hash = (hash << 2).wrapping_add(both);
+            if lone & filter == 0 {
+                found.push((position, lone));
+            }
+            if hash & filter == 0 {
+                found.push((position + 1, hash));
+            }
+        }
+        if let [last] = rest {
+            hash = (hash << 1).wrapping_add(gear[*last as usize]);
+            if hash & filter == 0 {
+                found.push((to - 1, hash));
+            }
+        }
+        found
+    }
+
+    // Find the chunk starting at `start`, as with `cut_gear()`, returning its
+    // hash and length. The hash of the first 64 bytes after the minimum size
+    // depends on where the scan began, and is computed here, while the cut
+    // points beyond that are taken from the candidates.
+    fn stitch(&self, data: &[u8], start: usize, candidates: &[(usize, u64)]) -> (u64, usize) {
+        let gear: &[u64; 256] = self.gear[..].try_into().unwrap();
+        let gear_ls: &[u64; 256] = self.gear_ls[..].try_into().unwrap();
+        debug_assert!(gear.iter().zip(gear_ls).all(|(g, ls)| g << 1 == *ls));
+        let mut remaining = data.len() - start;
+        if remaining <= self.min_size {
+            return (0, remaining);
+        }
+        let mut center = self.avg_size;
+        if remaining > self.max_size {
+            remaining = self.max_size;
+        } else if remaining < center {
+            center = remaining;
+        }
+        let src = &data[start..start + remaining];
+        let limit1 = center / 2;
+        let limit2 = remaining / 2;
+        let masks = |index: usize| {
+            if index < limit1 {
+                (self.mask_s, self.mask_s_ls)
+            } else {
+                (self.mask_l, self.mask_l_ls)
+            }
+        };
+        let first = self.min_size / 2;
+        let warm = limit2.min(first + WINDOW / 2);
+        let mut hash: u64 = 0;
+        for index in first..warm {
+            let (mask, mask_ls) = masks(index);
+            let a = index * 2;
+            hash = (hash << 2).wrapping_add(gear_ls[src[a] as usize]);
+            if (hash & mask_ls) == 0 {
+                return (hash, a);
+            }
+            hash = hash.wrapping_add(gear[src[a + 1] as usize]);
+            if (hash & mask) == 0 {
+                return (hash, a + 1);
+            }
+        }
+        if warm == limit2 {
+            return (hash, remaining);
+        }
+        // beyond the first 64 bytes, the hash after the second byte of a pair
+        // is that of the candidate, and after the first byte it is shifted
+        let from = start + warm * 2;
+        let to = start + limit2 * 2;
+        let skip = candidates.partition_point(|c| c.0 < from);
+        for (position, hash) in candidates[skip..].iter().take_while(|c| c.0 < to) {
+            let offset = position - start;
+            let (mask, mask_ls) = masks(offset / 2);
+            if offset.is_multiple_of(2) {
+                if ((hash << 1) & mask_ls) == 0 {
+                    return (hash << 1, offset);
+                }
+            } else if (hash & mask) == 0 {
+                return (*hash, offset);
+            }
+        }
+        let mut hash: u64 = 0;
+        for byte in &data[to - WINDOW..to] {
+            hash = (hash << 1).wrapping_add(gear[*byte as usize]);
+        }
+        (hash, remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gear::GearTable;
+    use crate::test_util::noise;
+    use std::fs;
+
+    fn compare(chunker: FastCDC) {
+        let expected: Vec<Chunk> = chunker.clone().collect();
+        for segment in [1000, 4096, 65536, 1 << 20] {
+            assert_eq!(chunker.clone().par_collect_with(segment), expected);
+        }
+        assert_eq!(chunker.par_collect(), expected);
+    }
+
+    #[test]
+    fn test_par_collect_sekien() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        compare(FastCDC::new(&contents, 4096, 16384, 65535));
+        compare(FastCDC::new(&contents, 64, 256, 1024));
+        compare(FastCDC::new(&contents, 8192, 16384, 32768));
+        let mut chunker = FastCDC::new(&contents, 4096, 16384, 65535);
+        chunker.next();
+        compare(chunker);
+    }
+
+    #[test]
+    fn test_par_collect_tables() {
+        let contents = noise(1 << 20, 0x1234_5678_9abc_def0);
+        for level in [Normalization::Level0, Normalization::Level2] {
+            compare(FastCDC::with_level(&contents, 2048, 8192, 65536, level));
+            compare(FastCDC::with_level_and_seed(
+                &contents, 1024, 4096, 16384, level, 666,
+            ));
+            compare(FastCDC::with_level_and_key(
+                &contents, 4096, 16384, 65535, level, &[7; 16],
+            ));
+            let table = GearTable::from_splitmix(42);
+            compare(FastCDC::with_level_and_gear(
+                &contents, 256, 1024, 8192, level, &table,
+            ));
+        }
+    }
+
+    #[test]
+    fn test_par_collect_edge_cases() {
+        let zeros = vec![0u8; 300_000];
+        compare(FastCDC::new(&zeros, 4096, 16384, 65535));
+        compare(FastCDC::new(&zeros[..5000], 4096, 16384, 65535));
+        compare(FastCDC::new(&zeros[..0], 4096, 16384, 65535));
+        let contents = noise(100_000, 42);
+        for length in [64, 65, 127, 128, 129, 4097, 4160, 4161, 16384, 65535, 65600] {
+            compare(FastCDC::new(&contents[..length], 64, 4096, 65535));
+            compare(FastCDC::new(&contents[..length], 4096, 16384, 65535));
+        }
+    }
+}
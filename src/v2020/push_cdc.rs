@@ -0,0 +1,351 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+use super::*;
+
+///
+/// The FastCDC chunker implementation from 2020, driven by pushing blocks of
+/// data into it, rather than by pulling data from a slice or a [`Read`].
+///
+/// Use `new` to construct an instance, then call [`update()`] with each block
+/// of data as it arrives, and [`finish()`] once the input has ended. Both
+/// methods invoke the given function with each completed chunk, in order. The
+/// cut points are identical to those of [`FastCDC`] over the concatenation of
+/// all of the blocks, regardless of how the input was divided.
+///
+/// A cut point is only determined once `max_size` bytes following the start
+/// of the chunk are available, hence the chunker retains up to `max_size` bytes
+/// between calls. When nothing is retained, chunks are found directly within
+/// the given block, and lent from it without copying.
+///
+/// [`update()`]: PushCDC::update
+/// [`finish()`]: PushCDC::finish
+///
+/// ```
+/// # use fastcdc::v2020::PushCDC;
+/// let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+/// let mut chunker = PushCDC::new(4096, 16384, 65535);
+/// let mut lengths = Vec::new();
+/// for packet in contents.chunks(1500) {
+///     chunker.update(packet, |chunk| lengths.push(chunk.length()));
+/// }
+/// chunker.finish(|chunk| lengths.push(chunk.length()));
+/// assert_eq!(lengths, [21325, 17140, 28084, 18217, 24700]);
+/// ```
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PushCDC {
+    /// Data retained from previous blocks, never more than `max_size` bytes.
+    buffer: Vec<u8>,
+    /// Number of bytes returned in chunks so far.
+    processed: u64,
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+    mask_s_ls: u64,
+    mask_l_ls: u64,
+    gear: Table,
+    gear_ls: Table,
+}
+
+impl PushCDC {
+    ///
+    /// Construct a [`PushCDC`] with the given chunk sizes.
+    ///
+    /// Uses chunk size normalization level 1 by default.
+    ///
+    pub fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::with_level(min_size, avg_size, max_size, Normalization::Level1)
+    }
+
+    ///
+    /// Create a new [`PushCDC`] with the given normalization level.
+    ///
+    pub fn with_level(
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+    ) -> Self {
+        Self::with_level_and_seed(min_size, avg_size, max_size, level, 0)
+    }
+
+    ///
+    /// Create a new [`PushCDC`] with the given normalization level and seed to
+    /// be XOR'd with the values in the gear tables.
+    ///
+    pub fn with_level_and_seed(
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        seed: u64,
+    ) -> Self {
+        debug_assert!(min_size >= MINIMUM_MIN);
+        debug_assert!(min_size <= MINIMUM_MAX);
+        debug_assert!(avg_size >= AVERAGE_MIN);
+        debug_assert!(avg_size <= AVERAGE_MAX);
+        debug_assert!(max_size >= MAXIMUM_MIN);
+        debug_assert!(max_size <= MAXIMUM_MAX);
+        let bits = logarithm2(avg_size);
+        let normalization = level.bits();
+        let mask_s = MASKS[(bits + normalization) as usize];
+        let mask_l = MASKS[(bits - normalization) as usize];
+        let (gear, gear_ls) = gear_with_seed(seed);
+        Self {
+            buffer: Vec::with_capacity(max_size),
+            processed: 0,
+            min_size,
+            avg_size,
+            max_size,
+            mask_s,
+            mask_l,
+            mask_s_ls: mask_s << 1,
+            mask_l_ls: mask_l << 1,
+            gear,
+            gear_ls,
+        }
+    }
+
+    ///
+    /// Create a new [`PushCDC`] with the given normalization level and the gear
+    /// tables derived from the given secret key by [`get_gear_with_key()`].
+    ///
+    pub fn with_level_and_key(
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        key: &[u8; 16],
+    ) -> Self {
+        let (gear, gear_ls) = gear_with_key(key);
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`PushCDC`] with the given normalization level and gear
+    /// table, in place of the built-in table.
+    ///
+    pub fn with_level_and_gear(
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        gear: &GearTable,
+    ) -> Self {
+        let (gear, gear_ls) = gear.tables();
+        Self {
+            gear,
+            gear_ls,
+            ..Self::with_level(min_size, avg_size, max_size, level)
+        }
+    }
+
+    ///
+    /// Create a new [`PushCDC`] using the validated parameters of the given
+    /// [`ChunkerConfig`].
+    ///
+    pub fn with_config(config: &ChunkerConfig) -> Result<Self, ConfigError> {
+        Ok(Self::with_level_and_seed(
+            config.min_size(),
+            config.avg_size(),
+            config.max_size(),
+            config.normalization(),
+            config.seed(),
+        ))
+    }
+
+    /// Find the next cut point in the given data, returning the hash and the
+    /// length of the chunk.
+    fn cut(&self, source: &[u8]) -> (u64, usize) {
+        cut_gear(
+            source,
+            self.min_size,
+            self.avg_size,
+            self.max_size,
+            self.mask_s,
+            self.mask_l,
+            self.mask_s_ls,
+            self.mask_l_ls,
+            &self.gear,
+            &self.gear_ls,
+        )
+    }
+
+    ///
+    /// Process the next block of input, invoking `emit` for every chunk that
+    /// is completed by this data.
+    ///
+    /// Blocks may be of any size, including empty. Data that does not yet form
+    /// a complete chunk is retained until the next call to `update()` or
+    /// `finish()`.
+    ///
+    pub fn update<F>(&mut self, mut data: &[u8], mut emit: F)
+    where
+        F: FnMut(ChunkRef<'_>),
+    {
+        while !data.is_empty() {
+            if self.buffer.is_empty() {
+                // nothing retained, find chunks directly within the input
+                while data.len() >= self.max_size {
+                    let (hash, count) = self.cut(data);
+                    emit(ChunkRef {
+                        hash,
+                        offset: self.processed,
+                        data: &data[..count],
+                    });
+                    self.processed += count as u64;
+                    data = &data[count..];
+                }
+                self.buffer.extend_from_slice(data);
+                return;
+            }
+            let retained = self.buffer.len();
+            let take = (self.max_size - retained).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            if self.buffer.len() < self.max_size {
+                return;
+            }
+            let (hash, count) = self.cut(&self.buffer);
+            emit(ChunkRef {
+                hash,
+                offset: self.processed,
+                data: &self.buffer[..count],
+            });
+            self.processed += count as u64;
+            if count >= retained {
+                // the chunk ended within the new data, the rest of which is
+                // still available from the input
+                self.buffer.clear();
+                data = &data[count - retained..];
+            } else {
+                self.buffer.drain(..count);
+                data = &data[take..];
+            }
+        }
+    }
+
+    ///
+    /// Signal the end of the input, invoking `emit` for each of the remaining
+    /// chunks.
+    ///
+    pub fn finish<F>(mut self, mut emit: F)
+    where
+        F: FnMut(ChunkRef<'_>),
+    {
+        let mut start = 0;
+        while start < self.buffer.len() {
+            let (hash, count) = self.cut(&self.buffer[start..]);
+            emit(ChunkRef {
+                hash,
+                offset: self.processed,
+                data: &self.buffer[start..start + count],
+            });
+            self.processed += count as u64;
+            start += count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn push_all(chunker: PushCDC, contents: &[u8], block_size: usize) -> Vec<Chunk> {
+        let mut chunker = chunker;
+        let mut results = Vec::new();
+        let mut record = |chunk: ChunkRef<'_>| {
+            let offset = chunk.offset as usize;
+            assert_eq!(chunk.data, &contents[offset..offset + chunk.length()]);
+            results.push(Chunk {
+                hash: chunk.hash,
+                offset,
+                length: chunk.length(),
+            });
+        };
+        for block in contents.chunks(block_size) {
+            chunker.update(block, &mut record);
+            chunker.update(&[], &mut record);
+        }
+        chunker.finish(&mut record);
+        results
+    }
+
+    #[test]
+    fn test_push_sekien_any_block_size() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        for block_size in [1, 7, 1500, 16384, 65535, 65536, 100_000, contents.len()] {
+            let chunker = PushCDC::new(4096, 16384, 65535);
+            let results = push_all(chunker, &contents, block_size);
+            assert_eq!(results, expected, "block size {block_size}");
+        }
+    }
+
+    #[test]
+    fn test_push_small_chunks() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let config = ChunkerConfig::builder(64, 256, 1024)
+            .normalization(Normalization::Level2)
+            .seed(666)
+            .try_build()
+            .unwrap();
+        let expected: Vec<Chunk> = FastCDC::with_config(&contents, &config).unwrap().collect();
+        for block_size in [3, 1000, 1025, 4096] {
+            let chunker = PushCDC::with_config(&config).unwrap();
+            let results = push_all(chunker, &contents, block_size);
+            assert_eq!(results, expected, "block size {block_size}");
+        }
+    }
+
+    #[test]
+    fn test_push_with_key() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let key = [7u8; 16];
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_key(&contents, 4096, 16384, 65535, Normalization::Level1, &key)
+                .collect();
+        let chunker = PushCDC::with_level_and_key(4096, 16384, 65535, Normalization::Level1, &key);
+        let results = push_all(chunker, &contents, 1500);
+        assert_eq!(results, expected);
+        let plain: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        assert_ne!(results, plain);
+    }
+
+    #[test]
+    fn test_push_with_level_and_gear() {
+        let contents = fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let level = Normalization::Level2;
+        let table = GearTable::from_splitmix(42);
+        let expected: Vec<Chunk> =
+            FastCDC::with_level_and_gear(&contents, 4096, 16384, 65535, level, &table).collect();
+        let chunker = PushCDC::with_level_and_gear(4096, 16384, 65535, level, &table);
+        let results = push_all(chunker, &contents, 1500);
+        assert_eq!(results, expected);
+        let plain: Vec<Chunk> = FastCDC::new(&contents, 4096, 16384, 65535).collect();
+        assert_ne!(results, plain);
+    }
+
+    #[test]
+    fn test_push_all_zeros() {
+        let contents = vec![0u8; 10_000];
+        let expected: Vec<Chunk> = FastCDC::new(&contents, 64, 256, 1024).collect();
+        let results = push_all(PushCDC::new(64, 256, 1024), &contents, 333);
+        assert_eq!(results.len(), 10);
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_push_empty() {
+        let results = push_all(PushCDC::new(64, 256, 1024), &[], 1);
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,579 @@
+//
+// Copyright (c) 2026 Nathan Fiedler
+//
+
+//! Vectorized implementation of [`cut_gear()`](super::cut_gear) for x86_64,
+//! using AVX-512 or AVX2 when the processor supports them.
+//!
+//! The scalar loop is bound by the latency of the hash computation, as each
+//! value depends on the one before it. However, the gear hash is shifted left
+//! by two bits for every pair of bytes, hence the value of the hash at any
+//! position depends only on the preceding 32 pairs (64 bytes). This allows for
+//! splitting a block of the source into one segment per vector lane, each of
+//! which is scanned independently of the others, after warming up the hash of
+//! the lane with the 32 pairs preceding the segment. The first cut point found
+//! in the earliest segment is the one the scalar loop would have found, with
+//! the identical hash value.
+
+use std::arch::x86_64::*;
+use std::ops::ControlFlow;
+
+// Number of byte pairs scanned by each lane in a block; must be a multiple of
+// 4, as each lane reads 8 bytes at a time, and at least the 32 pairs needed
+// to warm up the hash of a lane.
+const SEGMENT: usize = 256;
+
+// Number of byte pairs over which the hash is warmed up.
+const WINDOW: usize = 32;
+
+// Smallest average chunk size for which each vector loop is used, as measured
+// by the `cut_gear` group of the benchmarks. For smaller chunks, the work
+// wasted scanning past the cut point at the end of a block outweighs the
+// benefit of scanning several segments at once.
+const AVX512_MIN_AVG_SIZE: usize = 16384;
+const AVX2_MIN_AVG_SIZE: usize = 65536;
+
+///
+/// Find the next chunk cut point as with the scalar `cut_gear_arr()`, using
+/// the best vector instructions available, or `None` if the processor does
+/// not support any of them, or the average size is too small for the vector
+/// loop to be of benefit.
+///
+#[allow(clippy::too_many_arguments)]
+pub(super) fn cut_gear(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+    mask_s_ls: u64,
+    mask_l_ls: u64,
+    gear: &[u64; 256],
+    gear_ls: &[u64; 256],
+) -> Option<(u64, usize)> {
+    let masks = Masks {
+        mask_s,
+        mask_l,
+        mask_s_ls,
+        mask_l_ls,
+    };
+    if avg_size < AVX512_MIN_AVG_SIZE {
+        None
+    } else if is_x86_feature_detected!("avx512f") {
+        // SAFETY: the processor supports the required instructions
+        Some(unsafe { cut_avx512(source, min_size, avg_size, max_size, &masks, gear, gear_ls) })
+    } else if avg_size >= AVX2_MIN_AVG_SIZE && is_x86_feature_detected!("avx2") {
+        // SAFETY: the processor supports the required instructions
+        Some(unsafe { cut_avx2(source, min_size, avg_size, max_size, &masks, gear, gear_ls) })
+    } else {
+        None
+    }
+}
+
+struct Masks {
+    mask_s: u64,
+    mask_l: u64,
+    mask_s_ls: u64,
+    mask_l_ls: u64,
+}
+
+#[target_feature(enable = "avx512f")]
+fn cut_avx512(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    masks: &Masks,
+    gear: &[u64; 256],
+    gear_ls: &[u64; 256],
+) -> (u64, usize) {
+    // SAFETY: the caller ensured that AVX-512F is available
+    unsafe { cut::<Avx512>(source, min_size, avg_size, max_size, masks, gear, gear_ls) }
+}
+
+#[target_feature(enable = "avx2")]
+fn cut_avx2(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    masks: &Masks,
+    gear: &[u64; 256],
+    gear_ls: &[u64; 256],
+) -> (u64, usize) {
+    // SAFETY: the caller ensured that AVX2 is available
+    unsafe { cut::<Avx2>(source, min_size, avg_size, max_size, masks, gear, gear_ls) }
+}
+
+// Same bounds and mask selection as the scalar `cut_gear_arr()`.
+#[inline(always)]
+unsafe fn cut<V: Vector>(
+    source: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    masks: &Masks,
+    gear: &[u64; 256],
+    gear_ls: &[u64; 256],
+) -> (u64, usize) {
+    let mut remaining = source.len();
+    if remaining <= min_size {
+        return (0, remaining);
+    }
+    let mut center = avg_size;
+    if remaining > max_size {
+        remaining = max_size;
+    } else if remaining < center {
+        center = remaining;
+    }
+    let src = &source[..remaining];
+    let start = min_size / 2;
+    let limit1 = center / 2;
+    let limit2 = remaining / 2;
+    let scanner = Scanner { src, gear, gear_ls };
+    let mut hash = 0;
+    let regions = [
+        (start, limit1, masks.mask_s, masks.mask_s_ls),
+        (start.max(limit1), limit2, masks.mask_l, masks.mask_l_ls),
+    ];
+    for (from, to, mask, mask_ls) in regions {
+        if from < to {
+            // SAFETY: the caller ensured that the vector instructions are available
+            match unsafe { scanner.scan::<V>(hash, from, to, mask, mask_ls) } {
+                ControlFlow::Break(cut) => return cut,
+                ControlFlow::Continue(last) => hash = last,
+            }
+        }
+    }
+    (hash, remaining)
+}
+
+struct Scanner<'a> {
+    src: &'a [u8],
+    gear: &'a [u64; 256],
+    gear_ls: &'a [u64; 256],
+}
+
+impl Scanner<'_> {
+    ///
+    /// Scan the byte pairs from `from` up to `to` for a cut point, given the
+    /// hash as of the pair preceding `from`, breaking with the hash and
+    /// position of the cut point, or continuing with the hash as of the last
+    /// pair if there is none.
+    ///
+    #[inline(always)]
+    unsafe fn scan<V: Vector>(
+        &self,
+        mut hash: u64,
+        from: usize,
+        to: usize,
+        mask: u64,
+        mask_ls: u64,
+    ) -> ControlFlow<(u64, usize), u64> {
+        let mut index = from;
+        while to - index >= V::LANES * SEGMENT {
+            // SAFETY: the caller ensured that the vector instructions are available
+            hash = unsafe { self.scan_block::<V>(hash, index, mask, mask_ls) }?;
+            index += V::LANES * SEGMENT;
+        }
+        // the remainder is too small for a block, continue with the scalar loop
+        while index < to {
+            let a = index * 2;
+            hash = (hash << 2).wrapping_add(self.gear_ls[self.src[a] as usize]);
+            if (hash & mask_ls) == 0 {
+                return ControlFlow::Break((hash, a));
+            }
+            hash = hash.wrapping_add(self.gear[self.src[a + 1] as usize]);
+            if (hash & mask) == 0 {
+                return ControlFlow::Break((hash, a + 1));
+            }
+            index += 1;
+        }
+        ControlFlow::Continue(hash)
+    }
+
+    ///
+    /// Scan a block of `LANES * SEGMENT` byte pairs starting at `from`, with
+    /// each lane scanning one segment of the block.
+    ///
+    #[inline(always)]
+    unsafe fn scan_block<V: Vector>(
+        &self,
+        hash: u64,
+        from: usize,
+        mask: u64,
+        mask_ls: u64,
+    ) -> ControlFlow<(u64, usize), u64> {
+        let source = self.src.as_ptr();
+        let gear = self.gear.as_ptr();
+        let gear_ls = self.gear_ls.as_ptr();
+        // SAFETY: the caller ensured that the vector instructions are
+        // available, and every byte read lies within the block, or within the
+        // window preceding each segment other than the first
+        unsafe {
+            let eight = V::splat(8);
+            // Warm up the hash of each lane but the first with the pairs that
+            // precede its segment, all of which are within the block, as the
+            // segments are longer than the window. The first lane (which reads
+            // the same bytes as the second) takes the hash as given.
+            let second = 2 * (from + SEGMENT - WINDOW);
+            let mut offsets = V::segments(second.wrapping_sub(2 * SEGMENT), 2 * SEGMENT);
+            offsets = offsets.with_first(second as u64);
+            let mut state = V::splat(0);
+            for _ in 0..WINDOW / 4 {
+                let words = V::gather_bytes(source, offsets);
+                for shift in [0, 16, 32, 48] {
+                    let even = V::gather(gear_ls, words.byte(shift));
+                    let odd = V::gather(gear, words.byte(shift + 8));
+                    state = state.shl2().add(even).add(odd);
+                }
+                offsets = offsets.add(eight);
+            }
+            state = state.with_first(hash);
+
+            let mask_v = V::splat(mask);
+            let mask_ls_v = V::splat(mask_ls);
+            let mut offsets = V::segments(2 * from, 2 * SEGMENT);
+            let mut pending = (1u32 << V::LANES) - 1;
+            let mut found = [(0u64, 0usize); 16];
+            for word in 0..SEGMENT / 4 {
+                let words = V::gather_bytes(source, offsets);
+                for (pair, shift) in [0, 16, 32, 48].into_iter().enumerate() {
+                    let even = V::gather(gear_ls, words.byte(shift));
+                    let odd = V::gather(gear, words.byte(shift + 8));
+                    let hash_ls = state.shl2().add(even);
+                    state = hash_ls.add(odd);
+                    let hits_ls = hash_ls.zero_lanes(mask_ls_v) & pending;
+                    let hits = state.zero_lanes(mask_v) & pending;
+                    if (hits_ls | hits) != 0 {
+                        let values_ls = hash_ls.to_array();
+                        let values = state.to_array();
+                        for lane in 0..V::LANES {
+                            let bit = 1 << lane;
+                            let index = 2 * (from + lane * SEGMENT + word * 4 + pair);
+                            if hits_ls & bit != 0 {
+                                found[lane] = (values_ls[lane], index);
+                            } else if hits & bit != 0 {
+                                found[lane] = (values[lane], index + 1);
+                            }
+                        }
+                        pending &= !(hits_ls | hits);
+                        if pending & 1 == 0 {
+                            // nothing precedes the first segment
+                            return ControlFlow::Break(found[0]);
+                        }
+                    }
+                }
+                offsets = offsets.add(eight);
+            }
+            let done = !pending & ((1u32 << V::LANES) - 1);
+            if done != 0 {
+                return ControlFlow::Break(found[done.trailing_zeros() as usize]);
+            }
+            ControlFlow::Continue(state.to_array()[V::LANES - 1])
+        }
+    }
+}
+
+///
+/// Operations on a vector of 64-bit lanes, implemented for each instruction
+/// set. The functions are only safe to call when the processor supports the
+/// corresponding instructions.
+///
+trait Vector: Copy {
+    /// Number of 64-bit lanes in the vector.
+    const LANES: usize;
+
+    /// Vector with every lane set to `value`.
+    unsafe fn splat(value: u64) -> Self;
+
+    /// Vector with lane `n` set to `first + n * step` (wrapping).
+    unsafe fn segments(first: usize, step: usize) -> Self;
+
+    /// Replace the value of the first lane.
+    unsafe fn with_first(self, value: u64) -> Self;
+
+    /// Read 8 bytes (little-endian) at each of the byte offsets from `base`.
+    unsafe fn gather_bytes(base: *const u8, offsets: Self) -> Self;
+
+    /// Look up each lane in the 256-entry table.
+    unsafe fn gather(table: *const u64, indices: Self) -> Self;
+
+    /// The byte at the given bit position of each lane.
+    unsafe fn byte(self, shift: i32) -> Self;
+
+    unsafe fn shl2(self) -> Self;
+
+    unsafe fn add(self, other: Self) -> Self;
+
+    /// Bit mask of the lanes for which `self & mask` is zero.
+    unsafe fn zero_lanes(self, mask: Self) -> u32;
+
+    unsafe fn to_array(self) -> [u64; 16];
+}
+
+// The operations on vectors below are only called from functions compiled for
+// the corresponding instruction set, into which they are inlined.
+
+#[derive(Clone, Copy)]
+struct Avx2(__m256i);
+
+impl Vector for Avx2 {
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        unsafe { Avx2(_mm256_set1_epi64x(value as i64)) }
+    }
+
+    #[inline(always)]
+    unsafe fn segments(first: usize, step: usize) -> Self {
+        let lane = |n: usize| first.wrapping_add(n * step) as i64;
+        unsafe { Avx2(_mm256_set_epi64x(lane(3), lane(2), lane(1), lane(0))) }
+    }
+
+    #[inline(always)]
+    unsafe fn with_first(self, value: u64) -> Self {
+        unsafe {
+            let first = _mm256_set1_epi64x(value as i64);
+            Avx2(_mm256_blend_epi32::<0b0000_0011>(self.0, first))
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn gather_bytes(base: *const u8, offsets: Self) -> Self {
+        // SAFETY: the caller ensured that every offset is in bounds
+        unsafe { Avx2(_mm256_i64gather_epi64::<1>(base as *const i64, offsets.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn gather(table: *const u64, indices: Self) -> Self {
+        // SAFETY: the indices are bytes, and the table has 256 entries
+        unsafe { Avx2(_mm256_i64gather_epi64::<8>(table as *const i64, indices.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn byte(self, shift: i32) -> Self {
+        unsafe {
+            let shifted = _mm256_srl_epi64(self.0, _mm_cvtsi32_si128(shift));
+            Avx2(_mm256_and_si256(shifted, _mm256_set1_epi64x(0xff)))
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn shl2(self) -> Self {
+        unsafe { Avx2(_mm256_slli_epi64::<2>(self.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        unsafe { Avx2(_mm256_add_epi64(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn zero_lanes(self, mask: Self) -> u32 {
+        unsafe {
+            let masked = _mm256_and_si256(self.0, mask.0);
+            let zero = _mm256_cmpeq_epi64(masked, _mm256_setzero_si256());
+            _mm256_movemask_pd(_mm256_castsi256_pd(zero)) as u32
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn to_array(self) -> [u64; 16] {
+        let mut values = [0u64; 16];
+        // SAFETY: the array has room for all of the lanes
+        unsafe { _mm256_storeu_si256(values.as_mut_ptr() as *mut __m256i, self.0) };
+        values
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Avx512(__m512i);
+
+impl Vector for Avx512 {
+    const LANES: usize = 8;
+
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        unsafe { Avx512(_mm512_set1_epi64(value as i64)) }
+    }
+
+    #[inline(always)]
+    unsafe fn segments(first: usize, step: usize) -> Self {
+        let lane = |n: usize| first.wrapping_add(n * step) as i64;
+        unsafe {
+            Avx512(_mm512_set_epi64(
+                lane(7),
+                lane(6),
+                lane(5),
+                lane(4),
+                lane(3),
+                lane(2),
+                lane(1),
+                lane(0),
+            ))
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn with_first(self, value: u64) -> Self {
+        unsafe { Avx512(_mm512_mask_set1_epi64(self.0, 1, value as i64)) }
+    }
+
+    #[inline(always)]
+    unsafe fn gather_bytes(base: *const u8, offsets: Self) -> Self {
+        // SAFETY: the caller ensured that every offset is in bounds
+        unsafe { Avx512(_mm512_i64gather_epi64::<1>(offsets.0, base as *const i64)) }
+    }
+
+    #[inline(always)]
+    unsafe fn gather(table: *const u64, indices: Self) -> Self {
+        // SAFETY: the indices are bytes, and the table has 256 entries
+        unsafe { Avx512(_mm512_i64gather_epi64::<8>(indices.0, table as *const i64)) }
+    }
+
+    #[inline(always)]
+    unsafe fn byte(self, shift: i32) -> Self {
+        unsafe {
+            let shifted = _mm512_srl_epi64(self.0, _mm_cvtsi32_si128(shift));
+            Avx512(_mm512_and_si512(shifted, _mm512_set1_epi64(0xff)))
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn shl2(self) -> Self {
+        unsafe { Avx512(_mm512_slli_epi64::<2>(self.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        unsafe { Avx512(_mm512_add_epi64(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    unsafe fn zero_lanes(self, mask: Self) -> u32 {
+        unsafe { _mm512_testn_epi64_mask(self.0, mask.0) as u32 }
+    }
+
+    #[inline(always)]
+    unsafe fn to_array(self) -> [u64; 16] {
+        let mut values = [0u64; 16];
+        // SAFETY: the array has room for all of the lanes
+        unsafe { _mm512_storeu_si512(values.as_mut_ptr() as *mut __m512i, self.0) };
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::noise;
+    use crate::v2020::{GEAR, MASKS, cut_gear_arr, get_gear_with_key, get_gear_with_seed};
+
+    // Scan the whole source with both implementations, comparing every cut.
+    fn compare(
+        source: &[u8],
+        sizes: (usize, usize, usize),
+        level: u32,
+        tables: (&[u64; 256], &[u64; 256]),
+    ) -> usize {
+        let (min_size, avg_size, max_size) = sizes;
+        let bits = (avg_size as f64).log2().round() as u32;
+        let mask_s = MASKS[(bits + level) as usize];
+        let mask_l = MASKS[(bits - level) as usize];
+        let masks = Masks {
+            mask_s,
+            mask_l,
+            mask_s_ls: mask_s << 1,
+            mask_l_ls: mask_l << 1,
+        };
+        let (gear, gear_ls) = tables;
+        let mut start = 0;
+        let mut count = 0;
+        while start < source.len() {
+            let src = &source[start..];
+            let expected = cut_gear_arr(
+                src,
+                min_size,
+                avg_size,
+                max_size,
+                masks.mask_s,
+                masks.mask_l,
+                masks.mask_s_ls,
+                masks.mask_l_ls,
+                gear,
+                gear_ls,
+            );
+            if is_x86_feature_detected!("avx2") {
+                let actual =
+                    unsafe { cut_avx2(src, min_size, avg_size, max_size, &masks, gear, gear_ls) };
+                assert_eq!(actual, expected, "avx2 at offset {start}");
+            }
+            if is_x86_feature_detected!("avx512f") {
+                let actual =
+                    unsafe { cut_avx512(src, min_size, avg_size, max_size, &masks, gear, gear_ls) };
+                assert_eq!(actual, expected, "avx512 at offset {start}");
+            }
+            start += expected.1;
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn test_sekien_matches_scalar() {
+        let contents = std::fs::read("test/fixtures/SekienAkashita.jpg").unwrap();
+        let tables = (&GEAR, &crate::v2020::GEAR_LS);
+        for sizes in [
+            (4096, 16384, 65535),
+            (8192, 32768, 131072),
+            (16384, 65536, 262144),
+            (1024, 4096, 16384),
+            (64, 256, 1024),
+        ] {
+            for level in 0..=3 {
+                compare(&contents, sizes, level, tables);
+            }
+        }
+    }
+
+    #[test]
+    fn test_noise_matches_scalar() {
+        let (gear, gear_ls) = get_gear_with_seed(666);
+        let seeded: (&[u64; 256], &[u64; 256]) = (
+            gear[..].try_into().unwrap(),
+            gear_ls[..].try_into().unwrap(),
+        );
+        let (gear, gear_ls) = get_gear_with_key(&[3u8; 16]);
+        let keyed: (&[u64; 256], &[u64; 256]) = (
+            gear[..].try_into().unwrap(),
+            gear_ls[..].try_into().unwrap(),
+        );
+        let contents = noise(1_000_000, 0x9e37_79b9_7f4a_7c15);
+        for tables in [(&GEAR, &crate::v2020::GEAR_LS), seeded, keyed] {
+            let count = compare(&contents, (4096, 16384, 65535), 1, tables);
+            assert!(count > 30);
+            compare(&contents, (2048, 8192, 32768), 2, tables);
+            compare(&contents, (16384, 65536, 262144), 0, tables);
+        }
+    }
+
+    #[test]
+    fn test_edge_cases_match_scalar() {
+        let tables = (&GEAR, &crate::v2020::GEAR_LS);
+        // every chunk is the maximum size, and the final hash is returned
+        compare(&[0u8; 300_000], (4096, 16384, 65535), 1, tables);
+        // odd lengths, and lengths around the size of a block
+        let contents = noise(70_000, 42);
+        for length in [
+            4095, 4096, 4097, 8191, 16383, 16385, 20481, 40961, 65535, 65537,
+        ] {
+            compare(&contents[..length], (4096, 16384, 65535), 1, tables);
+            compare(&contents[..length], (64, 16384, 65535), 1, tables);
+        }
+        // a minimum larger than the average leaves only the second region
+        compare(&contents, (16384, 8192, 65535), 1, tables);
+    }
+}